@@ -45,7 +45,11 @@ fn main() {
 
     let gpu_output = GpuImage::<Rgba8UintNorm>::new(&fw, WIDTH as u32, HEIGHT as u32); // Shader output
 
-    let shader = gpgpu::Shader::from_wgsl_file(&fw, "examples/webcam/shader.wgsl").unwrap();
+    // The shader is reloaded whenever `shader.wgsl` is saved, so it can be tweaked while running
+    let watcher = gpgpu::watcher::ShaderWatcher::new(&fw);
+    let shader = watcher
+        .watch_wgsl_file("examples/webcam/shader.wgsl")
+        .unwrap();
 
     let desc = DescriptorSet::default()
         .bind_const_image(&gpu_input)
@@ -65,6 +69,13 @@ fn main() {
     while window.is_open() && !window.is_key_down(Key::Escape) {
         let fps = std::time::Instant::now();
 
+        // Invalid shader edits are reported and the last valid shader keeps running
+        for reload in watcher.poll() {
+            if let Err(err) = reload.result {
+                println!("\n{}", err);
+            }
+        }
+
         // Adapted for new (using image 0.24) nokhwa version
         let cam_raw = camera.frame().unwrap();
         let cam_buf = cam_raw.decode_image::<RgbAFormat>().unwrap(); // Obtain cam current frame
@@ -0,0 +1,58 @@
+//! Crate-wide error type.
+//!
+//! Every module of `gpgpu` returns its own specific error (e.g. [`BufferError`] or
+//! [`ImageOutputError`]), which keeps the possible failures of each operation precise.
+//! All of them convert into [`Error`], so applications mixing several kinds of
+//! operations can propagate them with `?` into a single error type.
+
+#[cfg(feature = "integrate-encase")]
+use crate::features::integrate_encase::LayoutError;
+#[cfg(feature = "integrate-ndarray")]
+use crate::features::integrate_ndarray::{ArrayError, NdarrayError};
+use crate::{
+    framework::FrameworkError,
+    kernel::ShaderError,
+    memory::OutOfBudget,
+    primitives::{
+        buffers::BufferError,
+        images::{ImageBindError, ImageCopyError, ImageInputError, ImageOutputError},
+        samplers::SamplerError,
+    },
+};
+
+/// Result type using the crate-wide [`Error`].
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Any error returned by `gpgpu`.
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    FrameworkError(#[from] FrameworkError),
+    #[error(transparent)]
+    BufferError(#[from] BufferError),
+    #[error(transparent)]
+    ImageOutputError(#[from] ImageOutputError),
+    #[error(transparent)]
+    ImageInputError(#[from] ImageInputError),
+    #[error(transparent)]
+    ImageCopyError(#[from] ImageCopyError),
+    #[error(transparent)]
+    ImageBindError(#[from] ImageBindError),
+    #[error(transparent)]
+    SamplerError(#[from] SamplerError),
+    #[error(transparent)]
+    OutOfBudget(#[from] OutOfBudget),
+    #[error(transparent)]
+    ShaderError(#[from] ShaderError),
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[cfg(feature = "integrate-encase")]
+    #[error(transparent)]
+    LayoutError(#[from] LayoutError),
+    #[cfg(feature = "integrate-ndarray")]
+    #[error(transparent)]
+    NdarrayError(#[from] NdarrayError),
+    #[cfg(feature = "integrate-ndarray")]
+    #[error(transparent)]
+    ArrayError(#[from] ArrayError),
+}
@@ -6,3 +6,9 @@ pub mod integrate_image;
 
 #[cfg(feature = "integrate-ndarray")]
 pub mod integrate_ndarray;
+
+#[cfg(feature = "integrate-encase")]
+pub mod integrate_encase;
+
+#[cfg(feature = "integrate-tokio")]
+pub mod integrate_tokio;
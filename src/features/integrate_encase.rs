@@ -0,0 +1,517 @@
+use std::marker::PhantomData;
+
+use encase::{
+    internal::{CreateFrom, WriteInto},
+    ShaderType, StorageBuffer, UniformBuffer,
+};
+use thiserror::Error;
+use wgpu::util::DeviceExt;
+
+use crate::{
+    kernel::ShaderError,
+    memory::{Allocation, MemoryKind, OutOfBudget},
+    primitives::buffers::{self, BufferError},
+    DescriptorSet, Framework, GpuBufferUsage, Shader,
+};
+
+pub use encase;
+
+#[derive(Error, Debug)]
+pub enum LayoutError {
+    #[error(transparent)]
+    EncaseError(#[from] encase::internal::Error),
+    #[error(transparent)]
+    BufferError(#[from] BufferError),
+    #[error(transparent)]
+    ShaderError(#[from] ShaderError),
+    #[error("No reflected binding at group {group}, binding {binding}.")]
+    BindingNotFound { group: u32, binding: u32 },
+    #[error("Layout mismatch at group {group}, binding {binding} (shader size {shader} bytes, host size {host} bytes).")]
+    SizeMismatch {
+        group: u32,
+        binding: u32,
+        shader: u64,
+        host: u64,
+    },
+    #[error("Layout mismatch at group {group}, binding {binding} (shader struct has {shader} members, host struct has {host} fields).")]
+    MemberCountMismatch {
+        group: u32,
+        binding: u32,
+        shader: usize,
+        host: usize,
+    },
+    #[error("Layout mismatch at group {group}, binding {binding} (member `{member}` at shader offset {shader}, host offset {host}).")]
+    OffsetMismatch {
+        group: u32,
+        binding: u32,
+        member: String,
+        shader: u64,
+        host: u64,
+    },
+    #[error("Layout mismatch at group {group}, binding {binding} (shader array stride {shader} bytes, host stride {host} bytes).")]
+    StrideMismatch {
+        group: u32,
+        binding: u32,
+        shader: u64,
+        host: u64,
+    },
+    #[error("Value does not fit the buffer (required size {required} bytes, current size {current} bytes).")]
+    ValueTooLarge { required: u64, current: u64 },
+    #[error(transparent)]
+    OutOfBudget(#[from] OutOfBudget),
+}
+
+pub type LayoutResult<T> = Result<T, LayoutError>;
+
+pub(crate) mod sealed {
+    use encase::private::{ArrayMetadata, MatrixMetadata, StructMetadata};
+
+    /// Layout of a host type, as computed by `encase`.
+    pub enum HostLayout {
+        /// Offsets of the fields of a struct.
+        Struct(Vec<u64>),
+        /// Stride of the elements of an array.
+        Array(u64),
+        /// Any other type, only compared by size.
+        Other,
+    }
+
+    /// Metadata of the [`ShaderType`](encase::ShaderType)s whose layout can be checked.
+    pub trait ExtraMetadata {
+        fn host_layout(&self) -> HostLayout;
+    }
+
+    impl ExtraMetadata for () {
+        fn host_layout(&self) -> HostLayout {
+            HostLayout::Other
+        }
+    }
+
+    impl ExtraMetadata for MatrixMetadata {
+        fn host_layout(&self) -> HostLayout {
+            HostLayout::Other
+        }
+    }
+
+    impl ExtraMetadata for ArrayMetadata {
+        fn host_layout(&self) -> HostLayout {
+            HostLayout::Array(self.stride.get())
+        }
+    }
+
+    impl<const N: usize> ExtraMetadata for StructMetadata<N> {
+        fn host_layout(&self) -> HostLayout {
+            HostLayout::Struct(self.offsets.to_vec())
+        }
+    }
+}
+
+/// Checks that the host layout of `S` matches the reflected layout of the
+/// resource bound at `group` and `binding` in `shader`.
+///
+/// Compares the total size, and the member offsets of structs or the stride of arrays.
+/// Fields nested deeper are only covered by the offsets and size of their parents.
+fn check_layout<S>(shader: &Shader, group: u32, binding: u32) -> LayoutResult<()>
+where
+    S: ShaderType,
+    S::ExtraMetadata: sealed::ExtraMetadata,
+{
+    use sealed::{ExtraMetadata, HostLayout};
+
+    let (module, var, layouter) = shader
+        .binding_layout(group, binding)?
+        .ok_or(LayoutError::BindingNotFound { group, binding })?;
+
+    let shader_size = layouter[var.ty].size as u64;
+    let host_size = S::min_size().get();
+    if shader_size != host_size {
+        return Err(LayoutError::SizeMismatch {
+            group,
+            binding,
+            shader: shader_size,
+            host: host_size,
+        });
+    }
+
+    match (&module.types[var.ty].inner, S::METADATA.extra.host_layout()) {
+        (naga::TypeInner::Struct { members, .. }, HostLayout::Struct(offsets)) => {
+            if members.len() != offsets.len() {
+                return Err(LayoutError::MemberCountMismatch {
+                    group,
+                    binding,
+                    shader: members.len(),
+                    host: offsets.len(),
+                });
+            }
+
+            let mismatch = members
+                .iter()
+                .zip(offsets)
+                .enumerate()
+                .find(|(_, (member, host))| member.offset as u64 != *host);
+
+            if let Some((index, (member, host))) = mismatch {
+                return Err(LayoutError::OffsetMismatch {
+                    group,
+                    binding,
+                    member: member.name.clone().unwrap_or_else(|| index.to_string()),
+                    shader: member.offset as u64,
+                    host,
+                });
+            }
+        }
+        (naga::TypeInner::Array { stride, .. }, HostLayout::Array(host))
+            if *stride as u64 != host =>
+        {
+            return Err(LayoutError::StrideMismatch {
+                group,
+                binding,
+                shader: *stride as u64,
+                host,
+            });
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Uniform buffer holding a single `S` value, laid out following the
+/// WGSL uniform address space rules (std140-like).
+///
+/// `S` must implement [`encase::ShaderType`], usually through its derive macro.
+///
+/// More information about its shader representation is
+/// under the [`DescriptorSet::bind_uniform_struct`](crate::DescriptorSet::bind_uniform_struct) documentation.
+pub struct GpuUniformStruct<S> {
+    fw: Framework,
+    buf: wgpu::Buffer,
+    label: Option<String>,
+    _allocation: Allocation,
+    marker: PhantomData<S>,
+}
+
+/// Storage buffer holding a single `S` value, laid out following the
+/// WGSL storage address space rules (std430-like).
+///
+/// `S` must implement [`encase::ShaderType`], usually through its derive macro.
+/// Its last field may be a runtime-sized array.
+///
+/// More information about its shader representation is
+/// under the [`DescriptorSet::bind_storage_struct`](crate::DescriptorSet::bind_storage_struct) documentation.
+pub struct GpuStorageStruct<S> {
+    fw: Framework,
+    buf: wgpu::Buffer,
+    label: Option<String>,
+    _allocation: Allocation,
+    marker: PhantomData<S>,
+}
+
+impl<S> GpuUniformStruct<S>
+where
+    S: ShaderType + WriteInto,
+{
+    /// Constructs a new [`GpuUniformStruct`] from a `value` with an optional `label`.
+    pub fn new(fw: &Framework, value: &S, label: Option<&str>) -> LayoutResult<Self> {
+        let mut contents = UniformBuffer::new(Vec::new());
+        contents.write(value)?;
+
+        let contents = contents.into_inner();
+        let allocation = fw.allocate(MemoryKind::Buffer, contents.len() as u64, label)?;
+
+        let buf = fw
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label,
+                contents: &contents,
+                usage: wgpu::BufferUsages::UNIFORM
+                    | wgpu::BufferUsages::COPY_SRC
+                    | wgpu::BufferUsages::COPY_DST,
+            });
+
+        Ok(Self {
+            fw: fw.clone(),
+            buf,
+            label: label.map(str::to_owned),
+            _allocation: allocation,
+            marker: PhantomData,
+        })
+    }
+
+    /// Constructs a new [`GpuUniformStruct`] like [`GpuUniformStruct::new`], after checking that the layout
+    /// of `S` matches the one of the uniform bound at `group` and `binding` in `shader`.
+    pub fn new_checked(
+        fw: &Framework,
+        value: &S,
+        label: Option<&str>,
+        shader: &Shader,
+        group: u32,
+        binding: u32,
+    ) -> LayoutResult<Self>
+    where
+        S::ExtraMetadata: sealed::ExtraMetadata,
+    {
+        Self::check_layout(shader, group, binding)?;
+        Self::new(fw, value, label)
+    }
+
+    /// Checks that the layout of `S` matches the one of the uniform
+    /// bound at `group` and `binding` in `shader`.
+    pub fn check_layout(shader: &Shader, group: u32, binding: u32) -> LayoutResult<()>
+    where
+        S::ExtraMetadata: sealed::ExtraMetadata,
+    {
+        check_layout::<S>(shader, group, binding)
+    }
+
+    /// Returns the debug label of the buffer, if any.
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    /// Writes `value` into this [`GpuUniformStruct`]. The operation is instantly offloaded.
+    pub fn write(&self, value: &S) -> LayoutResult<()> {
+        let mut contents = UniformBuffer::new(Vec::new());
+        contents.write(value)?;
+
+        self.fw
+            .queue
+            .write_buffer(&self.buf, 0, &contents.into_inner());
+
+        Ok(())
+    }
+
+    /// Pulls the value from the [`GpuUniformStruct`].
+    pub async fn read(&self) -> LayoutResult<S>
+    where
+        S: CreateFrom,
+    {
+        let download = buffers::download_buffer(
+            &self.fw,
+            &self.buf,
+            0..self.buf.size(),
+            self.label.as_deref(),
+        )
+        .await?;
+
+        Ok(UniformBuffer::new(&*download).create()?)
+    }
+
+    /// Blocking version of `GpuUniformStruct::read()`.
+    pub fn read_blocking(&self) -> LayoutResult<S>
+    where
+        S: CreateFrom,
+    {
+        self.fw.block_on(self.read())
+    }
+}
+
+impl<S> GpuStorageStruct<S>
+where
+    S: ShaderType + WriteInto,
+{
+    /// Constructs a new [`GpuStorageStruct`] from a `value` with an optional `label`.
+    ///
+    /// The buffer is sized to fit `value`, including its runtime-sized array, if any.
+    pub fn new(fw: &Framework, value: &S, label: Option<&str>) -> LayoutResult<Self> {
+        let mut contents = StorageBuffer::new(Vec::new());
+        contents.write(value)?;
+
+        let contents = contents.into_inner();
+        let allocation = fw.allocate(MemoryKind::Buffer, contents.len() as u64, label)?;
+
+        let buf = fw
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label,
+                contents: &contents,
+                usage: wgpu::BufferUsages::STORAGE
+                    | wgpu::BufferUsages::COPY_SRC
+                    | wgpu::BufferUsages::COPY_DST,
+            });
+
+        Ok(Self {
+            fw: fw.clone(),
+            buf,
+            label: label.map(str::to_owned),
+            _allocation: allocation,
+            marker: PhantomData,
+        })
+    }
+
+    /// Constructs a new [`GpuStorageStruct`] like [`GpuStorageStruct::new`], after checking that the layout
+    /// of `S` matches the one of the storage buffer bound at `group` and `binding` in `shader`.
+    pub fn new_checked(
+        fw: &Framework,
+        value: &S,
+        label: Option<&str>,
+        shader: &Shader,
+        group: u32,
+        binding: u32,
+    ) -> LayoutResult<Self>
+    where
+        S::ExtraMetadata: sealed::ExtraMetadata,
+    {
+        Self::check_layout(shader, group, binding)?;
+        Self::new(fw, value, label)
+    }
+
+    /// Checks that the layout of `S` matches the one of the storage buffer
+    /// bound at `group` and `binding` in `shader`.
+    ///
+    /// Runtime-sized arrays are compared using a single element.
+    pub fn check_layout(shader: &Shader, group: u32, binding: u32) -> LayoutResult<()>
+    where
+        S::ExtraMetadata: sealed::ExtraMetadata,
+    {
+        check_layout::<S>(shader, group, binding)
+    }
+
+    /// Returns the debug label of the buffer, if any.
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    /// Writes `value` into this [`GpuStorageStruct`]. The operation is instantly offloaded.
+    ///
+    /// Fails if `value` does not fit the buffer.
+    pub fn write(&self, value: &S) -> LayoutResult<()> {
+        let mut contents = StorageBuffer::new(Vec::new());
+        contents.write(value)?;
+        let contents = contents.into_inner();
+
+        if contents.len() as u64 > self.buf.size() {
+            return Err(LayoutError::ValueTooLarge {
+                required: contents.len() as u64,
+                current: self.buf.size(),
+            });
+        }
+
+        self.fw.queue.write_buffer(&self.buf, 0, &contents);
+
+        Ok(())
+    }
+
+    /// Pulls the value from the [`GpuStorageStruct`].
+    ///
+    /// Runtime-sized arrays are filled up to the buffer size.
+    pub async fn read(&self) -> LayoutResult<S>
+    where
+        S: CreateFrom,
+    {
+        let download = buffers::download_buffer(
+            &self.fw,
+            &self.buf,
+            0..self.buf.size(),
+            self.label.as_deref(),
+        )
+        .await?;
+
+        Ok(StorageBuffer::new(&*download).create()?)
+    }
+
+    /// Blocking version of `GpuStorageStruct::read()`.
+    pub fn read_blocking(&self) -> LayoutResult<S>
+    where
+        S: CreateFrom,
+    {
+        self.fw.block_on(self.read())
+    }
+}
+
+impl<'res> DescriptorSet<'res> {
+    /// Binds a [`GpuUniformStruct`] as a uniform buffer in the shader.
+    ///
+    /// ### Example WGSL syntax:
+    /// ```ignore
+    /// struct UniformStruct {
+    ///     a: vec3<u32>,
+    ///     b: f32,
+    /// }
+    ///
+    /// @group(0) @binding(0)
+    /// var<uniform> myUniformBuffer: UniformStruct;
+    /// ```
+    ///
+    /// ### Matching Rust struct:
+    /// ```ignore
+    /// #[derive(gpgpu::features::integrate_encase::encase::ShaderType)]
+    /// struct UniformStruct {
+    ///     a: glam::UVec3, // Requires encase `glam` feature
+    ///     b: f32,
+    /// }
+    /// ```
+    pub fn bind_uniform_struct<S>(mut self, uniform: &'res GpuUniformStruct<S>) -> Self
+    where
+        S: ShaderType,
+    {
+        let bind_id = self.set_layout.len() as u32;
+
+        let bind_entry = wgpu::BindGroupLayoutEntry {
+            binding: bind_id,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                has_dynamic_offset: false,
+                min_binding_size: Some(S::min_size()),
+                ty: wgpu::BufferBindingType::Uniform,
+            },
+            count: None,
+        };
+
+        let bind = wgpu::BindGroupEntry {
+            binding: bind_id,
+            resource: uniform.buf.as_entire_binding(),
+        };
+
+        self.set_layout.push(bind_entry);
+        self.binds.push(bind);
+
+        self
+    }
+
+    /// Binds a [`GpuStorageStruct`] as a storage buffer in the shader with a specific `usage`.
+    ///
+    /// ### Example WGSL syntax:
+    /// ```ignore
+    /// struct StorageStruct {
+    ///     count: u32,
+    ///     data: array<vec3<f32>>,
+    /// }
+    ///
+    /// @group(0) @binding(0)
+    /// var<storage, read_write> myStorageBuffer: StorageStruct;
+    /// ```
+    pub fn bind_storage_struct<S>(
+        mut self,
+        storage: &'res GpuStorageStruct<S>,
+        usage: GpuBufferUsage,
+    ) -> Self
+    where
+        S: ShaderType,
+    {
+        let bind_id = self.set_layout.len() as u32;
+
+        let bind_entry = wgpu::BindGroupLayoutEntry {
+            binding: bind_id,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                has_dynamic_offset: false,
+                min_binding_size: Some(S::min_size()),
+                ty: wgpu::BufferBindingType::Storage {
+                    read_only: usage == GpuBufferUsage::ReadOnly,
+                },
+            },
+            count: None,
+        };
+
+        let bind = wgpu::BindGroupEntry {
+            binding: bind_id,
+            resource: storage.buf.as_entire_binding(),
+        };
+
+        self.set_layout.push(bind_entry);
+        self.binds.push(bind);
+
+        self
+    }
+}
@@ -43,71 +43,95 @@ macro_rules! gpgpu_to_image_impl {
 }
 
 gpgpu_to_image_impl! {
-    ::image::Rgba<u8>, pixels::Rgba8Uint, pixels::Rgba8UintNorm;
+    ::image::Luma<u8>, pixels::R8Uint, pixels::R8UintNorm;
+    ::image::Luma<i8>, pixels::R8Sint, pixels::R8SintNorm;
+    ::image::Luma<u16>, pixels::R16Uint, pixels::R16UintNorm;
+    ::image::Luma<i16>, pixels::R16Sint, pixels::R16SintNorm;
+    ::image::Luma<u32>, pixels::R32Uint;
+    ::image::Luma<i32>, pixels::R32Sint;
+    ::image::Luma<f32>, pixels::R32Float;
+    ::image::LumaA<u8>, pixels::Rg8Uint, pixels::Rg8UintNorm;
+    ::image::LumaA<i8>, pixels::Rg8Sint, pixels::Rg8SintNorm;
+    ::image::LumaA<u16>, pixels::Rg16Uint, pixels::Rg16UintNorm;
+    ::image::LumaA<i16>, pixels::Rg16Sint, pixels::Rg16SintNorm;
+    ::image::LumaA<u32>, pixels::Rg32Uint;
+    ::image::LumaA<i32>, pixels::Rg32Sint;
+    ::image::LumaA<f32>, pixels::Rg32Float;
+    ::image::Rgba<u8>, pixels::Rgba8Uint, pixels::Rgba8UintNorm, pixels::Rgba8UintNormSrgb;
     ::image::Rgba<i8>, pixels::Rgba8Sint, pixels::Rgba8SintNorm;
+    ::image::Rgba<u16>, pixels::Rgba16Uint, pixels::Rgba16UintNorm;
+    ::image::Rgba<i16>, pixels::Rgba16Sint, pixels::Rgba16SintNorm;
+    ::image::Rgba<u32>, pixels::Rgba32Uint;
+    ::image::Rgba<i32>, pixels::Rgba32Sint;
     ::image::Rgba<f32>, pixels::Rgba32Float
-    // ::image::Luma<u8>, pixels::Luma8, pixels::Luma8Norm
 }
 
 image_to_gpgpu_impl! {
+    ::image::Luma<u8>, pixels::R8Uint, pixels::R8UintNorm;
+    ::image::Luma<i8>, pixels::R8Sint, pixels::R8SintNorm;
+    ::image::Luma<u16>, pixels::R16Uint, pixels::R16UintNorm;
+    ::image::Luma<i16>, pixels::R16Sint, pixels::R16SintNorm;
+    ::image::Luma<u32>, pixels::R32Uint, pixels::R32Uint;
+    ::image::Luma<i32>, pixels::R32Sint, pixels::R32Sint;
+    ::image::Luma<f32>, pixels::R32Float, pixels::R32Float;
+    ::image::LumaA<u8>, pixels::Rg8Uint, pixels::Rg8UintNorm;
+    ::image::LumaA<i8>, pixels::Rg8Sint, pixels::Rg8SintNorm;
+    ::image::LumaA<u16>, pixels::Rg16Uint, pixels::Rg16UintNorm;
+    ::image::LumaA<i16>, pixels::Rg16Sint, pixels::Rg16SintNorm;
+    ::image::LumaA<u32>, pixels::Rg32Uint, pixels::Rg32Uint;
+    ::image::LumaA<i32>, pixels::Rg32Sint, pixels::Rg32Sint;
+    ::image::LumaA<f32>, pixels::Rg32Float, pixels::Rg32Float;
     ::image::Rgba<u8>, pixels::Rgba8Uint, pixels::Rgba8UintNorm;
     ::image::Rgba<i8>, pixels::Rgba8Sint, pixels::Rgba8SintNorm;
+    ::image::Rgba<u16>, pixels::Rgba16Uint, pixels::Rgba16UintNorm;
+    ::image::Rgba<i16>, pixels::Rgba16Sint, pixels::Rgba16SintNorm;
+    ::image::Rgba<u32>, pixels::Rgba32Uint, pixels::Rgba32Uint;
+    ::image::Rgba<i32>, pixels::Rgba32Sint, pixels::Rgba32Sint;
     ::image::Rgba<f32>, pixels::Rgba32Float, pixels::Rgba32Float
-    // ::image::Luma<u8>, pixels::Luma8, pixels::Luma8Norm
 }
 
 type PixelContainer<P> = Vec<<<P as GpgpuToImage>::ImgPixel as image::Pixel>::Subpixel>;
 
-impl<'fw, Pixel> GpuImage<'fw, Pixel>
+impl<Pixel> GpuImage<Pixel>
 where
     Pixel: image::Pixel + ImageToGpgpu + 'static,
     Pixel::Subpixel: bytemuck::Pod,
 {
     /// Constructs a new [`GpuImage`] from a [`image::ImageBuffer`].
     pub fn from_image_buffer<Container>(
-        fw: &'fw crate::Framework,
+        fw: &crate::Framework,
         img: &ImageBuffer<Pixel, Container>,
-    ) -> GpuImage<'fw, Pixel::GpgpuPixel>
+    ) -> GpuImage<Pixel::GpgpuPixel>
     where
         Container: std::ops::Deref<Target = [Pixel::Subpixel]>,
     {
         let (width, height) = img.dimensions();
-        GpuImage::from_bytes(
-            fw,
-            bytemuck::cast_slice(img),
-            width * Pixel::GpgpuPixel::byte_size() as u32,
-            height,
-        )
+        GpuImage::from_bytes(fw, bytemuck::cast_slice(img), width, height)
     }
 
     /// Constructs a new normalised [`GpuImage`] from a [`image::ImageBuffer`].
     pub fn from_image_buffer_normalised<Container>(
-        fw: &'fw crate::Framework,
+        fw: &crate::Framework,
         img: &ImageBuffer<Pixel, Container>,
-    ) -> GpuImage<'fw, Pixel::NormGpgpuPixel>
+    ) -> GpuImage<Pixel::NormGpgpuPixel>
     where
         Container: std::ops::Deref<Target = [Pixel::Subpixel]>,
     {
         let (width, height) = img.dimensions();
-        GpuImage::from_bytes(
-            fw,
-            bytemuck::cast_slice(img),
-            width * Pixel::GpgpuPixel::byte_size() as u32,
-            height,
-        )
+        GpuImage::from_bytes(fw, bytemuck::cast_slice(img), width, height)
     }
 }
 
-impl<'fw, Pixel> GpuConstImage<'fw, Pixel>
+impl<Pixel> GpuConstImage<Pixel>
 where
     Pixel: image::Pixel + ImageToGpgpu + 'static,
     Pixel::Subpixel: bytemuck::Pod,
 {
     /// Constructs a new [`GpuConstImage`] from a [`image::ImageBuffer`].
     pub fn from_image_buffer<Container>(
-        fw: &'fw crate::Framework,
+        fw: &crate::Framework,
         img: &ImageBuffer<Pixel, Container>,
-    ) -> GpuConstImage<'fw, Pixel::GpgpuPixel>
+    ) -> GpuConstImage<Pixel::GpgpuPixel>
     where
         Container: std::ops::Deref<Target = [Pixel::Subpixel]>,
     {
@@ -117,23 +141,18 @@ where
 
     /// Constructs a new normalised [`GpuConstImage`] from a [`image::ImageBuffer`].
     pub fn from_image_buffer_normalised<Container>(
-        fw: &'fw crate::Framework,
+        fw: &crate::Framework,
         img: &ImageBuffer<Pixel, Container>,
-    ) -> GpuConstImage<'fw, Pixel::NormGpgpuPixel>
+    ) -> GpuConstImage<Pixel::NormGpgpuPixel>
     where
         Container: std::ops::Deref<Target = [Pixel::Subpixel]>,
     {
         let (width, height) = img.dimensions();
-        GpuConstImage::from_bytes(
-            fw,
-            bytemuck::cast_slice(img),
-            width * Pixel::GpgpuPixel::byte_size() as u32,
-            height,
-        )
+        GpuConstImage::from_bytes(fw, bytemuck::cast_slice(img), width, height)
     }
 }
 
-impl<'fw, P> GpuImage<'fw, P>
+impl<P> GpuImage<P>
 where
     P: PixelInfo + GpgpuToImage,
     <<P as GpgpuToImage>::ImgPixel as image::Pixel>::Subpixel: bytemuck::Pod,
@@ -158,7 +177,7 @@ where
             Vec<<<P as GpgpuToImage>::ImgPixel as image::Pixel>::Subpixel>,
         >,
     ) -> Result<usize, ImageOutputError> {
-        futures::executor::block_on(self.read_into_image_buffer(buf))
+        self.fw.block_on(self.read_into_image_buffer(buf))
     }
 
     /// Pulls all the pixels from the [`GpuImage`] into a [`image::ImageBuffer`].
@@ -183,7 +202,7 @@ where
     pub fn read_to_image_buffer_blocking(
         &self,
     ) -> Result<::image::ImageBuffer<P::ImgPixel, PixelContainer<P>>, ImageOutputError> {
-        futures::executor::block_on(self.read_to_image_buffer())
+        self.fw.block_on(self.read_to_image_buffer())
     }
 
     /// Writes a buffer into this [`GpuImage`], returning how many pixels were written. The operation is instantly offloaded.
@@ -202,7 +221,7 @@ where
     }
 }
 
-impl<'fw, P> GpuConstImage<'fw, P>
+impl<P> GpuConstImage<P>
 where
     P: PixelInfo + GpgpuToImage,
     <<P as GpgpuToImage>::ImgPixel as image::Pixel>::Subpixel: bytemuck::Pod,
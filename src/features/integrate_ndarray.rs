@@ -20,17 +20,14 @@ pub enum ArrayError {
 
 pub type ArrayResult<T> = Result<T, ArrayError>;
 
-pub struct GpuArray<'fw, T, D>(GpuBuffer<'fw, T>, D);
+pub struct GpuArray<T, D>(GpuBuffer<T>, D);
 
-impl<'fw, T, D> GpuArray<'fw, T, D>
+impl<T, D> GpuArray<T, D>
 where
     T: bytemuck::Pod,
     D: ndarray::Dimension,
 {
-    pub fn from_array(
-        fw: &'fw crate::Framework,
-        array: ndarray::ArrayView<T, D>,
-    ) -> ArrayResult<Self> {
+    pub fn from_array(fw: &crate::Framework, array: ndarray::ArrayView<T, D>) -> ArrayResult<Self> {
         let slice: Result<&[T], _> = array
             .as_slice_memory_order()
             .ok_or(NdarrayError::ArrayNotContiguous);
@@ -40,15 +37,21 @@ where
         Ok(Self(buf, array.raw_dim()))
     }
 
-    pub async fn read(&self) -> ArrayResult<ndarray::Array<T, D>> {
+    pub async fn read(&self) -> ArrayResult<ndarray::Array<T, D>>
+    where
+        T: Send + Sync,
+    {
         let v = self.0.read_vec().await?;
         ndarray::Array::from_shape_vec(self.1.clone(), v)
             .map_err(NdarrayError::InvalidShape)
             .map_err(ArrayError::NdarrayError)
     }
 
-    pub fn read_blocking(&self) -> ArrayResult<ndarray::Array<T, D>> {
-        futures::executor::block_on(self.read())
+    pub fn read_blocking(&self) -> ArrayResult<ndarray::Array<T, D>>
+    where
+        T: Send + Sync,
+    {
+        self.0.framework().block_on(self.read())
     }
 
     pub fn write(&self, array: ndarray::ArrayView<T, D>) -> ArrayResult<u64> {
@@ -59,7 +62,7 @@ where
         Ok(self.0.write(slice?)?)
     }
 
-    pub fn to_gpu_buffer(self) -> GpuBuffer<'fw, T> {
+    pub fn to_gpu_buffer(self) -> GpuBuffer<T> {
         self.0
     }
 }
@@ -0,0 +1,33 @@
+use crate::Framework;
+
+impl Framework {
+    /// Runs `f` with a clone of this [`Framework`] on the blocking thread pool of the
+    /// current tokio runtime.
+    ///
+    /// Useful to call the `*_blocking` methods of GPU primitives moved into `f`
+    /// without stalling the runtime worker threads.
+    ///
+    /// # Panics
+    /// If called outside of a tokio runtime, or if `f` panics.
+    pub async fn spawn_blocking<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(Framework) -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let fw = self.clone();
+
+        match tokio::task::spawn_blocking(move || f(fw)).await {
+            Ok(output) => output,
+            Err(err) => std::panic::resume_unwind(err.into_panic()),
+        }
+    }
+
+    /// Waits until the GPU has finished all the submitted work, blocking a thread
+    /// of the tokio blocking pool instead of the current task.
+    ///
+    /// # Panics
+    /// If called outside of a tokio runtime.
+    pub async fn wait_idle(&self) {
+        self.spawn_blocking(|fw| fw.poll_blocking()).await
+    }
+}
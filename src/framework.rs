@@ -1,9 +1,43 @@
-use std::{sync::Arc, time::Duration};
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+    task::{Context, Poll, Wake, Waker},
+    thread::{JoinHandle, Thread},
+    time::Duration,
+};
+
+use futures::FutureExt;
+use thiserror::Error;
 
 use crate::Framework;
 
+#[derive(Error, Debug)]
+pub enum FrameworkError {
+    #[error("No suitable GPU adapter was found.")]
+    AdapterNotFound,
+    #[error(transparent)]
+    RequestDeviceError(#[from] wgpu::RequestDeviceError),
+}
+
 impl Default for Framework {
+    /// Creates a [`Framework`] with the default GPU of the system.
+    ///
+    /// # Panics
+    /// Panics when no GPU adapter is available or the device cannot be created.
+    /// See [`Framework::try_default`] for a fallible version.
     fn default() -> Self {
+        Self::try_default().unwrap_or_else(|err| panic!("{}", err))
+    }
+}
+
+impl Framework {
+    /// Fallible version of [`Framework::default`].
+    ///
+    /// The power preference can be set with the `WGPU_POWER_PREF` environment variable.
+    pub fn try_default() -> Result<Self, FrameworkError> {
         let power_preference = wgpu::util::power_preference_from_env()
             .unwrap_or(wgpu::PowerPreference::HighPerformance);
         let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
@@ -20,18 +54,29 @@ impl Default for Framework {
                     ..Default::default()
                 })
                 .await
-                .expect("Failed at adapter creation.");
+                .ok_or(FrameworkError::AdapterNotFound)?;
 
-            Self::new(adapter, Duration::from_millis(10)).await
+            Self::try_new(adapter, Duration::from_millis(10)).await
         })
     }
-}
 
-impl Framework {
     /// Creates a new [`Framework`] instance from a [`wgpu::Adapter`] and a `polling_time`.
     ///
     /// Use this method when there are multiple GPUs in use or when a [`wgpu::Surface`] is required.
+    ///
+    /// # Panics
+    /// Panics when the device cannot be created. See [`Framework::try_new`] for a fallible version.
     pub async fn new(adapter: wgpu::Adapter, polling_time: Duration) -> Self {
+        Self::try_new(adapter, polling_time)
+            .await
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Fallible version of [`Framework::new`].
+    pub async fn try_new(
+        adapter: wgpu::Adapter,
+        polling_time: Duration,
+    ) -> Result<Self, FrameworkError> {
         let (device, queue) = adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
@@ -41,8 +86,7 @@ impl Framework {
                 },
                 None,
             )
-            .await
-            .expect("Failed at device creation.");
+            .await?;
 
         let info = adapter.get_info();
         log::info!(
@@ -53,18 +97,16 @@ impl Framework {
         );
 
         let device = Arc::new(device);
-        let polling_device = Arc::clone(&device);
+        let poller = Poller::spawn(Arc::clone(&device), polling_time);
 
-        std::thread::spawn(move || loop {
-            polling_device.poll(wgpu::Maintain::Poll);
-            std::thread::sleep(polling_time);
-        });
-
-        Self {
+        Ok(Self {
+            _poller: Arc::new(poller),
             device,
-            queue,
-            adapter,
-        }
+            queue: Arc::new(queue),
+            adapter: Arc::new(adapter),
+            memory: Default::default(),
+            image_kernels: Default::default(),
+        })
     }
 
     /// Gets info about the adapter that created this [`Framework`].
@@ -82,6 +124,23 @@ impl Framework {
         self.device.limits()
     }
 
+    /// Gets the capabilities of a texture `format` that may be used with this [`Framework`].
+    ///
+    /// Adapter specific capabilities are only reported when
+    /// [`wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES`] is enabled.
+    pub fn texture_format_features(
+        &self,
+        format: wgpu::TextureFormat,
+    ) -> wgpu::TextureFormatFeatures {
+        let features = self.features();
+
+        if features.contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES) {
+            self.adapter.get_texture_format_features(format)
+        } else {
+            format.guaranteed_format_features(features)
+        }
+    }
+
     /// Polls the device in a non-blocking manner.
     pub fn poll(&self) {
         self.device.poll(wgpu::Maintain::Poll);
@@ -91,4 +150,109 @@ impl Framework {
     pub fn poll_blocking(&self) {
         self.device.poll(wgpu::Maintain::Wait);
     }
+
+    /// Runs `future` to completion on the current thread, waiting on the device
+    /// whenever it is pending.
+    ///
+    /// Used by the `*_blocking` methods instead of a general purpose executor: submitted work
+    /// is driven by the current thread, so it does not stall when called from within an async
+    /// runtime. When waiting on the device did not wake the task, the thread is parked until
+    /// a later poll (e.g. by the polling thread) runs the callback that wakes it.
+    pub(crate) fn block_on<F: Future>(&self, future: F) -> F::Output {
+        let mut future = std::pin::pin!(future);
+        let thread_waker = Arc::new(ThreadWaker {
+            thread: std::thread::current(),
+            woken: AtomicBool::new(false),
+        });
+        let waker = Waker::from(Arc::clone(&thread_waker));
+        let mut cx = Context::from_waker(&waker);
+
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+
+            self.device.poll(wgpu::Maintain::Wait);
+
+            while !thread_waker.woken.swap(false, Ordering::Acquire) {
+                std::thread::park();
+            }
+        }
+    }
+
+    /// Polls `receiver` of a GPU callback, polling the device once when it is not ready yet.
+    ///
+    /// While pending, the task is woken by the callback itself, which runs on a later poll
+    /// of the device (e.g. by the polling thread), so waiting futures do not busy-loop.
+    pub(crate) fn poll_receiver<T>(
+        &self,
+        receiver: &mut futures::channel::oneshot::Receiver<T>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<T, futures::channel::oneshot::Canceled>> {
+        if let Poll::Ready(result) = receiver.poll_unpin(cx) {
+            return Poll::Ready(result);
+        }
+
+        self.device.poll(wgpu::Maintain::Poll);
+        receiver.poll_unpin(cx)
+    }
+}
+
+/// Thread polling the device of a [`Framework`] every `polling_time`.
+///
+/// Stopped and joined when the last clone of the [`Framework`] is dropped, so the device
+/// is released by the thread dropping it rather than by a thread still running at exit.
+pub(crate) struct Poller {
+    stop: Option<mpsc::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Poller {
+    fn spawn(device: Arc<wgpu::Device>, polling_time: Duration) -> Self {
+        let (stop, stopped) = mpsc::channel::<()>();
+
+        let thread = std::thread::spawn(move || loop {
+            device.poll(wgpu::Maintain::Poll);
+
+            match stopped.recv_timeout(polling_time) {
+                Err(mpsc::RecvTimeoutError::Timeout) => continue,
+                _ => break,
+            }
+        });
+
+        Self {
+            stop: Some(stop),
+            thread: Some(thread),
+        }
+    }
+}
+
+impl Drop for Poller {
+    fn drop(&mut self) {
+        drop(self.stop.take());
+
+        if let Some(thread) = self.thread.take() {
+            // A GPU callback run by the poller itself may drop the last framework clone.
+            if thread.thread().id() != std::thread::current().id() {
+                let _ = thread.join();
+            }
+        }
+    }
+}
+
+/// Waker unparking the thread running [`Framework::block_on`].
+struct ThreadWaker {
+    thread: Thread,
+    woken: AtomicBool,
+}
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.woken.store(true, Ordering::Release);
+        self.thread.unpark();
+    }
 }
@@ -1,14 +1,317 @@
-use std::{borrow::Cow, path::Path};
+use std::{
+    borrow::Cow,
+    fmt,
+    path::Path,
+    sync::{Arc, Mutex, PoisonError, RwLock, Weak},
+};
+
+use thiserror::Error;
 
 use crate::{
-    primitives::{BufOps, ImgOps, PixelInfo},
-    DescriptorSet, Framework, GpuBuffer, GpuBufferUsage, GpuConstImage, GpuImage, GpuUniformBuffer,
-    Kernel, Program, Shader,
+    preprocessor::ProcessedSource,
+    primitives::{images::ImageBindError, BufOps, ImgOps, PixelInfo},
+    DescriptorSet, Framework, GpuAppendBuffer, GpuAtomicCounter, GpuBuffer, GpuBufferUsage,
+    GpuConstImage, GpuConstImage3d, GpuConstImageArray, GpuImage, GpuImage3d, GpuImageArray,
+    GpuStructBuffer, GpuUniformBuffer, Kernel, Program, Shader,
 };
 
+pub type ShaderResult<T> = Result<T, ShaderError>;
+
+/// Position of a [`ShaderError`] in the shader source.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceLocation {
+    /// Name of the file containing the error, if known.
+    ///
+    /// Differs from the shader name when the error lies in an `#include`d file.
+    pub file: Option<String>,
+    /// 1-based line number.
+    pub line: u32,
+    /// 1-based column, in characters.
+    pub column: u32,
+}
+
+impl From<naga::SourceLocation> for SourceLocation {
+    /// Converts a location of naga, which does not know the file of the error.
+    fn from(location: naga::SourceLocation) -> Self {
+        Self {
+            file: None,
+            line: location.line_number,
+            column: location.line_position,
+        }
+    }
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}:", file)?;
+        }
+
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Error returned when a [`Shader`] cannot be loaded.
+///
+/// Preprocessing, parse and validation errors carry the `location` of the error in the
+/// source, when known, the offending source line as `snippet` and a pretty-printed
+/// `diagnostic` also shown by their [`Display`](std::fmt::Display) implementation.
+#[derive(Error, Debug)]
+pub enum ShaderError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(
+        "Shader {} could not be preprocessed:\n{diagnostic}",
+        .label.as_deref().unwrap_or("<unlabelled>")
+    )]
+    PreprocessError {
+        label: Option<String>,
+        location: Option<SourceLocation>,
+        snippet: Option<String>,
+        diagnostic: String,
+    },
+    #[error(
+        "Shader {} could not be parsed:\n{diagnostic}",
+        .label.as_deref().unwrap_or("<unlabelled>")
+    )]
+    ParseError {
+        label: Option<String>,
+        location: Option<SourceLocation>,
+        snippet: Option<String>,
+        diagnostic: String,
+    },
+    #[error(
+        "Shader {} is invalid:\n{diagnostic}",
+        .label.as_deref().unwrap_or("<unlabelled>")
+    )]
+    ValidationError {
+        label: Option<String>,
+        location: Option<SourceLocation>,
+        snippet: Option<String>,
+        diagnostic: String,
+    },
+    #[error(
+        "Shader {} cannot replace the shader of kernel {kernel}:\n{diagnostic}",
+        .label.as_deref().unwrap_or("<unlabelled>")
+    )]
+    IncompatibleKernel {
+        label: Option<String>,
+        kernel: String,
+        diagnostic: String,
+    },
+    #[error(
+        "Types of shader {} could not be laid out:\n{diagnostic}",
+        .label.as_deref().unwrap_or("<unlabelled>")
+    )]
+    TypeLayoutError {
+        label: Option<String>,
+        diagnostic: String,
+    },
+}
+
+impl ShaderError {
+    /// Returns the location of the error in the shader source, if known.
+    pub fn location(&self) -> Option<&SourceLocation> {
+        match self {
+            ShaderError::PreprocessError { location, .. }
+            | ShaderError::ParseError { location, .. }
+            | ShaderError::ValidationError { location, .. } => location.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Returns the source line containing the error, if known.
+    pub fn snippet(&self) -> Option<&str> {
+        match self {
+            ShaderError::PreprocessError { snippet, .. }
+            | ShaderError::ParseError { snippet, .. }
+            | ShaderError::ValidationError { snippet, .. } => snippet.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+/// Location, snippet and diagnostic of a [`ShaderError`].
+struct Diagnosis {
+    location: Option<SourceLocation>,
+    snippet: Option<String>,
+    diagnostic: String,
+}
+
+/// Text a shader module was parsed from, used to locate its errors.
+#[derive(Clone, Copy)]
+enum SourceText<'a> {
+    Plain(&'a str),
+    Processed(&'a ProcessedSource),
+}
+
+impl SourceText<'_> {
+    fn text(&self) -> &str {
+        match self {
+            SourceText::Plain(source) => source,
+            SourceText::Processed(processed) => processed.source(),
+        }
+    }
+
+    /// Diagnoses an error with `message` at `location` of the parsed text.
+    ///
+    /// Errors of plain sources are pretty-printed by `emit`, which receives the source text
+    /// and its name. Errors of preprocessed sources point at the original files instead.
+    fn diagnose(
+        &self,
+        name: Option<&str>,
+        message: String,
+        location: Option<naga::SourceLocation>,
+        emit: impl FnOnce(&str, &str) -> String,
+    ) -> Diagnosis {
+        match *self {
+            SourceText::Plain(source) => Diagnosis {
+                location: location.map(|location| SourceLocation {
+                    file: name.map(str::to_owned),
+                    ..location.into()
+                }),
+                snippet: location.and_then(|location| line_of(source, location.line_number)),
+                diagnostic: emit(source, name.unwrap_or("shader")),
+            },
+            SourceText::Processed(processed) => {
+                let location = location.map(|location| processed.locate(location));
+                let snippet = location
+                    .as_ref()
+                    .and_then(|location| processed.original_line(location));
+                let diagnostic = match &location {
+                    Some(location) => render_diagnostic(&message, location, snippet.as_deref()),
+                    None => message,
+                };
+
+                Diagnosis {
+                    location,
+                    snippet,
+                    diagnostic,
+                }
+            }
+        }
+    }
+}
+
+/// Returns the 1-based `line` of `source`.
+pub(crate) fn line_of(source: &str, line: u32) -> Option<String> {
+    source
+        .lines()
+        .nth(line.checked_sub(1)? as usize)
+        .map(str::to_owned)
+}
+
+/// Renders a diagnostic of `message` pointing at `location`, in the style of `naga` ones.
+pub(crate) fn render_diagnostic(
+    message: &str,
+    location: &SourceLocation,
+    snippet: Option<&str>,
+) -> String {
+    let mut diagnostic = format!("error: {}\n  ┌─ {}\n", message, location);
+
+    if let Some(snippet) = snippet {
+        let line = location.line.to_string();
+        let gutter = " ".repeat(line.len());
+        let marker = " ".repeat(location.column.saturating_sub(1) as usize);
+
+        diagnostic.push_str(&format!(
+            "{gutter} │\n{line} │ {snippet}\n{gutter} │ {marker}^\n",
+            gutter = gutter,
+            line = line,
+            snippet = snippet,
+            marker = marker
+        ));
+    }
+
+    diagnostic
+}
+
+/// Joins the messages of `err` and its sources, one per line.
+fn error_chain(err: &dyn std::error::Error) -> String {
+    let mut message = err.to_string();
+    let mut source = err.source();
+
+    while let Some(err) = source {
+        message.push_str("\n  ");
+        message.push_str(&err.to_string());
+        source = err.source();
+    }
+
+    message
+}
+
+/// Returns the shader capabilities enabled by the device `features` and `downlevel` flags,
+/// as `wgpu` does.
+fn capabilities(
+    features: wgpu::Features,
+    downlevel: wgpu::DownlevelFlags,
+) -> naga::valid::Capabilities {
+    use naga::valid::Capabilities;
+
+    let mut capabilities = Capabilities::empty();
+    let mapping = [
+        (wgpu::Features::PUSH_CONSTANTS, Capabilities::PUSH_CONSTANT),
+        (wgpu::Features::SHADER_F64, Capabilities::FLOAT64),
+        (
+            wgpu::Features::SHADER_PRIMITIVE_INDEX,
+            Capabilities::PRIMITIVE_INDEX,
+        ),
+        (
+            wgpu::Features::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING,
+            Capabilities::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING
+                | Capabilities::SAMPLER_NON_UNIFORM_INDEXING,
+        ),
+        (
+            wgpu::Features::UNIFORM_BUFFER_AND_STORAGE_TEXTURE_ARRAY_NON_UNIFORM_INDEXING,
+            Capabilities::UNIFORM_BUFFER_AND_STORAGE_TEXTURE_ARRAY_NON_UNIFORM_INDEXING,
+        ),
+        (
+            wgpu::Features::TEXTURE_FORMAT_16BIT_NORM,
+            Capabilities::STORAGE_TEXTURE_16BIT_NORM_FORMATS,
+        ),
+        (wgpu::Features::MULTIVIEW, Capabilities::MULTIVIEW),
+        (
+            wgpu::Features::SHADER_EARLY_DEPTH_TEST,
+            Capabilities::EARLY_DEPTH_TEST,
+        ),
+        (
+            wgpu::Features::DUAL_SOURCE_BLENDING,
+            Capabilities::DUAL_SOURCE_BLENDING,
+        ),
+    ];
+
+    for (feature, capability) in mapping.iter().copied() {
+        capabilities.set(capability, features.contains(feature));
+    }
+
+    capabilities.set(
+        Capabilities::CUBE_ARRAY_TEXTURES,
+        downlevel.contains(wgpu::DownlevelFlags::CUBE_ARRAY_TEXTURES),
+    );
+    capabilities.set(
+        Capabilities::MULTISAMPLED_SHADING,
+        downlevel.contains(wgpu::DownlevelFlags::MULTISAMPLED_SHADING),
+    );
+
+    capabilities
+}
+
 impl<'res> DescriptorSet<'res> {
+    /// Sets the debug label of the [`wgpu::BindGroup`] and [`wgpu::BindGroupLayout`]
+    /// created from this [`DescriptorSet`].
+    ///
+    /// Defaults to the [`Kernel`] label followed by the set index.
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
     /// Binds a [`GpuUniformBuffer`] as a uniform buffer in the shader.
     ///
+    /// The bytes of `T` are uploaded as they are, so `T` must already follow the WGSL
+    /// uniform layout rules (e.g. `vec3<u32>` is 16 bytes aligned). Enable the
+    /// `integrate-encase` feature to use layout-aware `GpuUniformStruct` instead.
+    ///
     /// ### Example WGSL syntax:
     /// ```ignore
     /// struct UniformStruct {
@@ -106,6 +409,105 @@ impl<'res> DescriptorSet<'res> {
         self
     }
 
+    /// Binds a [`GpuStructBuffer`] as a storage buffer in the shader with a specific `usage`.
+    ///
+    /// ### Example WGSL syntax:
+    /// ```ignore
+    /// struct Particle {
+    ///     position: vec2<f32>,
+    ///     velocity: vec2<f32>,
+    /// }
+    ///
+    /// struct Particles {
+    ///     count: u32,
+    ///     data: array<Particle>,
+    /// }
+    ///
+    /// @group(0) @binding(0)
+    /// var<storage, read_write> particles: Particles;
+    /// ```
+    ///
+    /// ### Example GLSL syntax:
+    /// ```glsl
+    /// layout (set=0, binding=0) buffer Particles {
+    ///     uint count;
+    ///     Particle data[];
+    /// };
+    /// ```
+    pub fn bind_struct_buffer<H, T>(
+        mut self,
+        struct_buf: &'res GpuStructBuffer<H, T>,
+        usage: GpuBufferUsage,
+    ) -> Self
+    where
+        H: bytemuck::Pod,
+        T: bytemuck::Pod,
+    {
+        let bind_id = self.set_layout.len() as u32;
+
+        let bind_entry = wgpu::BindGroupLayoutEntry {
+            binding: bind_id,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                has_dynamic_offset: false,
+                min_binding_size: None,
+                ty: wgpu::BufferBindingType::Storage {
+                    read_only: usage == GpuBufferUsage::ReadOnly,
+                },
+            },
+            count: None,
+        };
+
+        let bind = wgpu::BindGroupEntry {
+            binding: bind_id,
+            resource: struct_buf.as_binding_resource(),
+        };
+
+        self.set_layout.push(bind_entry);
+        self.binds.push(bind);
+
+        self
+    }
+
+    /// Binds a [`GpuAtomicCounter`] as a read-write storage buffer in the shader.
+    ///
+    /// ### Example WGSL syntax:
+    /// ```ignore
+    /// @group(0) @binding(0)
+    /// var<storage, read_write> myCounter: atomic<u32>;
+    /// ```
+    ///
+    /// ### Example GLSL syntax:
+    /// ```glsl
+    /// layout (set=0, binding=0) buffer myCounter {
+    ///     uint counter;
+    /// };
+    /// ```
+    pub fn bind_atomic_counter(self, counter: &'res GpuAtomicCounter) -> Self {
+        self.bind_buffer(&counter.buf, GpuBufferUsage::ReadWrite)
+    }
+
+    /// Binds a [`GpuAppendBuffer`] as a read-write storage buffer in the shader.
+    ///
+    /// See [`wgsl_append_buffer`](crate::primitives::atomics::wgsl_append_buffer) for a ready to use declaration.
+    ///
+    /// ### Example WGSL syntax:
+    /// ```ignore
+    /// struct AppendBuffer {
+    ///     count: atomic<u32>,
+    ///     data: array<u32>,
+    /// }
+    ///
+    /// @group(0) @binding(0)
+    /// var<storage, read_write> myAppendBuffer: AppendBuffer;
+    /// ```
+    pub fn bind_append_buffer<T>(self, append_buf: &'res GpuAppendBuffer<T>) -> Self
+    where
+        T: bytemuck::Pod,
+    {
+        self.bind_struct_buffer(&append_buf.buf, GpuBufferUsage::ReadWrite)
+    }
+
     /// Binds a [`GpuImage`] as a storage image in the shader.
     /// This image is write-only.
     /// ### Example WGSL syntax:
@@ -143,6 +545,97 @@ impl<'res> DescriptorSet<'res> {
         self
     }
 
+    /// Binds a [`GpuImage`] as a storage image in the shader.
+    /// This image is read-write, so it can be updated in place.
+    ///
+    /// Fails if the pixel format does not support read-write storage access
+    /// on this adapter (see [`GpuImage::supports_read_write`]).
+    /// ### Example WGSL syntax:
+    /// ```ignore
+    /// @group(0) @binding(0)
+    /// var myStorageImg: texture_storage_2d<r32float, read_write>;
+    /// ```
+    ///
+    /// ### Example GLSL syntax:
+    /// ```glsl
+    /// layout (set=0, binding=0, r32f) image2D myStorageImg;
+    /// ```
+    pub fn bind_image_read_write<P: PixelInfo>(
+        mut self,
+        img: &'res GpuImage<P>,
+    ) -> Result<Self, ImageBindError> {
+        if !img.supports_read_write() {
+            return Err(ImageBindError::ReadWriteUnsupported {
+                label: img.label().map(str::to_owned),
+                format: P::wgpu_format(),
+            });
+        }
+
+        let bind_id = self.set_layout.len() as u32;
+
+        let bind_entry = wgpu::BindGroupLayoutEntry {
+            binding: bind_id,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::StorageTexture {
+                access: wgpu::StorageTextureAccess::ReadWrite,
+                format: P::wgpu_format(),
+                view_dimension: wgpu::TextureViewDimension::D2,
+            },
+            count: None,
+        };
+
+        let bind = wgpu::BindGroupEntry {
+            binding: bind_id,
+            resource: img.as_binding_resource(),
+        };
+
+        self.set_layout.push(bind_entry);
+        self.binds.push(bind);
+
+        Ok(self)
+    }
+
+    /// Binds a [`GpuImage`] as a texture in the shader, so the output
+    /// of a previous pass can be sampled.
+    /// This image is read-only.
+    /// ### Example WGSL syntax:
+    /// ```ignore
+    /// @group(0) @binding(0)
+    /// var myTexture: texture_2d<f32>;
+    /// ```
+    ///
+    /// ### Example GLSL syntax:
+    /// ```glsl
+    /// layout (set=0, binding=0) texture2D myTexture;
+    /// ```
+    pub fn bind_image_as_texture<P>(mut self, img: &'res GpuImage<P>) -> Self
+    where
+        P: PixelInfo,
+    {
+        let bind_id = self.set_layout.len() as u32;
+
+        let bind_entry = wgpu::BindGroupLayoutEntry {
+            binding: bind_id,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Texture {
+                sample_type: P::texture_sample(&img.fw),
+                multisampled: false,
+                view_dimension: wgpu::TextureViewDimension::D2,
+            },
+            count: None,
+        };
+
+        let bind = wgpu::BindGroupEntry {
+            binding: bind_id,
+            resource: img.as_binding_resource(),
+        };
+
+        self.set_layout.push(bind_entry);
+        self.binds.push(bind);
+
+        self
+    }
+
     /// Binds a [`GpuConstImage`] as a texture in the shader.
     /// This image is read-only.
     /// ### Example WGSL syntax:
@@ -165,7 +658,7 @@ impl<'res> DescriptorSet<'res> {
             binding: bind_id,
             visibility: wgpu::ShaderStages::COMPUTE,
             ty: wgpu::BindingType::Texture {
-                sample_type: P::wgpu_texture_sample(),
+                sample_type: P::texture_sample(&img.fw),
                 multisampled: false,
                 view_dimension: wgpu::TextureViewDimension::D2,
             },
@@ -183,7 +676,209 @@ impl<'res> DescriptorSet<'res> {
         self
     }
 
+    /// Binds a [`GpuImage3d`] as a storage image in the shader.
+    /// This image is write-only.
+    /// ### Example WGSL syntax:
+    /// ```ignore
+    /// @group(0) @binding(0)
+    /// var myStorageVolume: texture_storage_3d<r32float, write>;
+    /// ```
+    ///
+    /// ### Example GLSL syntax:
+    /// ```glsl
+    /// layout (set=0, binding=0, r32f) writeonly image3D myStorageVolume;
+    /// ```
+    pub fn bind_image_3d<P: PixelInfo>(self, img: &'res GpuImage3d<P>) -> Self {
+        self.bind_storage_texture(
+            img.as_binding_resource(),
+            P::wgpu_format(),
+            wgpu::TextureViewDimension::D3,
+        )
+    }
+
+    /// Binds a [`GpuImageArray`] as a storage image array in the shader.
+    /// This image is write-only.
+    /// ### Example WGSL syntax:
+    /// ```ignore
+    /// @group(0) @binding(0)
+    /// var myStorageFrames: texture_storage_2d_array<rgba8unorm, write>;
+    /// ```
+    ///
+    /// ### Example GLSL syntax:
+    /// ```glsl
+    /// layout (set=0, binding=0, rgba8) writeonly image2DArray myStorageFrames;
+    /// ```
+    pub fn bind_image_array<P: PixelInfo>(self, img: &'res GpuImageArray<P>) -> Self {
+        self.bind_storage_texture(
+            img.as_binding_resource(),
+            P::wgpu_format(),
+            wgpu::TextureViewDimension::D2Array,
+        )
+    }
+
+    /// Binds a [`GpuConstImage3d`] as a 3D texture in the shader.
+    /// This image is read-only.
+    /// ### Example WGSL syntax:
+    /// ```ignore
+    /// @group(0) @binding(0)
+    /// var myVolume: texture_3d<f32>;
+    /// ```
+    ///
+    /// ### Example GLSL syntax:
+    /// ```glsl
+    /// layout (set=0, binding=0) texture3D myVolume;
+    /// ```
+    pub fn bind_const_image_3d<P: PixelInfo>(self, img: &'res GpuConstImage3d<P>) -> Self {
+        self.bind_texture(
+            img.as_binding_resource(),
+            P::texture_sample(&img.fw),
+            wgpu::TextureViewDimension::D3,
+        )
+    }
+
+    /// Binds a [`GpuConstImageArray`] as a 2D texture array in the shader.
+    /// This image is read-only.
+    /// ### Example WGSL syntax:
+    /// ```ignore
+    /// @group(0) @binding(0)
+    /// var myFrames: texture_2d_array<f32>;
+    /// ```
+    ///
+    /// ### Example GLSL syntax:
+    /// ```glsl
+    /// layout (set=0, binding=0) texture2DArray myFrames;
+    /// ```
+    pub fn bind_const_image_array<P: PixelInfo>(self, img: &'res GpuConstImageArray<P>) -> Self {
+        self.bind_texture(
+            img.as_binding_resource(),
+            P::texture_sample(&img.fw),
+            wgpu::TextureViewDimension::D2Array,
+        )
+    }
+
+    /// Binds the mip `level` of a [`GpuConstImage`] as a texture in the shader.
+    /// This image level is read-only.
+    ///
+    /// Same shader representation as [`DescriptorSet::bind_const_image`].
+    pub fn bind_const_image_mip<P: PixelInfo>(
+        self,
+        img: &'res GpuConstImage<P>,
+        level: u32,
+    ) -> Result<Self, ImageBindError> {
+        let view = img.mip_views.get(level as usize).ok_or_else(|| {
+            ImageBindError::MipLevelOutOfBounds {
+                label: img.label().map(str::to_owned),
+                level,
+                levels: img.mip_level_count(),
+            }
+        })?;
+
+        Ok(self.bind_texture(
+            wgpu::BindingResource::TextureView(view),
+            P::texture_sample(&img.fw),
+            wgpu::TextureViewDimension::D2,
+        ))
+    }
+
+    /// Binds the mip `level` of a [`GpuConstImage`] as a storage image in the shader.
+    /// This image level is write-only.
+    ///
+    /// Only mipmapped images whose pixel format supports storage access can be bound.
+    /// Same shader representation as [`DescriptorSet::bind_image`].
+    pub fn bind_const_image_mip_storage<P: PixelInfo>(
+        self,
+        img: &'res GpuConstImage<P>,
+        level: u32,
+    ) -> Result<Self, ImageBindError> {
+        let view = img.mip_views.get(level as usize).ok_or_else(|| {
+            ImageBindError::MipLevelOutOfBounds {
+                label: img.label().map(str::to_owned),
+                level,
+                levels: img.mip_level_count(),
+            }
+        })?;
+
+        if !img
+            .as_gpu_texture()
+            .usage()
+            .contains(wgpu::TextureUsages::STORAGE_BINDING)
+        {
+            return Err(ImageBindError::StorageUnsupported {
+                label: img.label().map(str::to_owned),
+            });
+        }
+
+        Ok(self.bind_storage_texture(
+            wgpu::BindingResource::TextureView(view),
+            P::wgpu_format(),
+            wgpu::TextureViewDimension::D2,
+        ))
+    }
+
+    fn bind_storage_texture(
+        mut self,
+        resource: wgpu::BindingResource<'res>,
+        format: wgpu::TextureFormat,
+        view_dimension: wgpu::TextureViewDimension,
+    ) -> Self {
+        let bind_id = self.set_layout.len() as u32;
+
+        let bind_entry = wgpu::BindGroupLayoutEntry {
+            binding: bind_id,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::StorageTexture {
+                access: wgpu::StorageTextureAccess::WriteOnly,
+                format,
+                view_dimension,
+            },
+            count: None,
+        };
+
+        let bind = wgpu::BindGroupEntry {
+            binding: bind_id,
+            resource,
+        };
+
+        self.set_layout.push(bind_entry);
+        self.binds.push(bind);
+
+        self
+    }
+
+    fn bind_texture(
+        mut self,
+        resource: wgpu::BindingResource<'res>,
+        sample_type: wgpu::TextureSampleType,
+        view_dimension: wgpu::TextureViewDimension,
+    ) -> Self {
+        let bind_id = self.set_layout.len() as u32;
+
+        let bind_entry = wgpu::BindGroupLayoutEntry {
+            binding: bind_id,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Texture {
+                sample_type,
+                multisampled: false,
+                view_dimension,
+            },
+            count: None,
+        };
+
+        let bind = wgpu::BindGroupEntry {
+            binding: bind_id,
+            resource,
+        };
+
+        self.set_layout.push(bind_entry);
+        self.binds.push(bind);
+
+        self
+    }
+
     /// Binds a [`Sampler`] as a sampler object in the shader.
+    ///
+    /// Samplers with a comparison function (see [`SamplerBuilder::compare`](crate::primitives::samplers::SamplerBuilder::compare))
+    /// must be declared as `sampler_comparison`.
     /// ### Example WGSL syntax:
     /// ```ignore
     /// [[group(0), binding(0)]]
@@ -192,10 +887,7 @@ impl<'res> DescriptorSet<'res> {
     pub fn bind_sampler(mut self, sampler: &'res crate::Sampler) -> Self {
         let bind_id = self.set_layout.len() as u32;
 
-        let bind_type = match sampler.filter_mode {
-            crate::SamplerFilterMode::Linear => wgpu::SamplerBindingType::Filtering,
-            crate::SamplerFilterMode::Nearest => wgpu::SamplerBindingType::NonFiltering,
-        };
+        let bind_type = sampler.binding_type;
 
         let bind_entry = wgpu::BindGroupLayoutEntry {
             binding: bind_id,
@@ -218,38 +910,38 @@ impl<'res> DescriptorSet<'res> {
 
 impl Shader {
     /// Initialises a [`Shader`] from a SPIR-V file.
-    pub fn from_spirv_file(fw: &Framework, path: impl AsRef<Path>) -> std::io::Result<Self> {
+    pub fn from_spirv_file(fw: &Framework, path: impl AsRef<Path>) -> ShaderResult<Self> {
         let bytes = std::fs::read(&path)?;
         let shader_name = path.as_ref().to_str();
 
-        Ok(Self::from_spirv_bytes(fw, &bytes, shader_name))
+        Self::from_spirv_bytes(fw, &bytes, shader_name)
     }
 
     /// Initialises a [`Shader`] from SPIR-V bytes with an optional `name`.
-    pub fn from_spirv_bytes(fw: &Framework, bytes: &[u8], name: Option<&str>) -> Self {
-        let source = wgpu::util::make_spirv(bytes);
-
-        let shader = fw
-            .device
-            .create_shader_module(wgpu::ShaderModuleDescriptor {
-                label: name,
-                source,
-            });
+    pub fn from_spirv_bytes(
+        fw: &Framework,
+        bytes: &[u8],
+        name: Option<&str>,
+    ) -> ShaderResult<Self> {
+        let module =
+            naga::front::spv::parse_u8_slice(bytes, &Default::default()).map_err(|err| {
+                ShaderError::ParseError {
+                    label: name.map(str::to_owned),
+                    location: None,
+                    snippet: None,
+                    diagnostic: err.to_string(),
+                }
+            })?;
 
-        Self(shader)
+        Self::from_module(fw, module, None, name)
     }
 
     /// Initialises a [`Shader`] from a `WGSL` file.
-    pub fn from_wgsl_file(fw: &Framework, path: impl AsRef<Path>) -> std::io::Result<Self> {
+    pub fn from_wgsl_file(fw: &Framework, path: impl AsRef<Path>) -> ShaderResult<Self> {
         let source_string = std::fs::read_to_string(&path)?;
         let shader_name = path.as_ref().to_str();
 
-        Ok(Self(fw.device.create_shader_module(
-            wgpu::ShaderModuleDescriptor {
-                label: shader_name,
-                source: wgpu::ShaderSource::Wgsl(Cow::Owned(source_string)),
-            },
-        )))
+        Self::from_wgsl_string(fw, source_string, shader_name)
     }
 
     /// Initialises a [`Shader`] from a `WGSL` string.
@@ -257,13 +949,221 @@ impl Shader {
         fw: &Framework,
         source: String,
         name: Option<&str>,
-    ) -> std::io::Result<Self> {
-        Ok(Self(fw.device.create_shader_module(
-            wgpu::ShaderModuleDescriptor {
+    ) -> ShaderResult<Self> {
+        Self::from_wgsl_source(fw, SourceText::Plain(&source), name)
+    }
+
+    /// Initialises a [`Shader`] from `WGSL` processed by a
+    /// [`WgslPreprocessor`](crate::preprocessor::WgslPreprocessor).
+    ///
+    /// Errors point at the original files and lines of the processed source.
+    pub fn from_processed_wgsl(fw: &Framework, processed: &ProcessedSource) -> ShaderResult<Self> {
+        Self::from_wgsl_source(fw, SourceText::Processed(processed), processed.name())
+    }
+
+    fn from_wgsl_source(
+        fw: &Framework,
+        source: SourceText<'_>,
+        name: Option<&str>,
+    ) -> ShaderResult<Self> {
+        let module = naga::front::wgsl::parse_str(source.text()).map_err(|err| {
+            let diagnosis = source.diagnose(
+                name,
+                err.message().to_owned(),
+                err.location(source.text()),
+                |text, path| err.emit_to_string_with_path(text, path),
+            );
+
+            ShaderError::ParseError {
+                label: name.map(str::to_owned),
+                location: diagnosis.location,
+                snippet: diagnosis.snippet,
+                diagnostic: diagnosis.diagnostic,
+            }
+        })?;
+
+        Self::from_module(fw, module, Some(source), name)
+    }
+
+    /// Initialises a GLSL compute [`Shader`] from a file, with additional `#define`s.
+    ///
+    /// See [`Shader::from_glsl_string`].
+    #[cfg(feature = "glsl")]
+    pub fn from_glsl_file(
+        fw: &Framework,
+        path: impl AsRef<Path>,
+        defines: &[(&str, &str)],
+    ) -> ShaderResult<Self> {
+        let source_string = std::fs::read_to_string(&path)?;
+        let shader_name = path.as_ref().to_str();
+
+        Self::from_glsl_string(fw, source_string, defines, shader_name)
+    }
+
+    /// Initialises a GLSL compute [`Shader`] from a string, with additional `#define`s.
+    ///
+    /// Each `(name, value)` of `defines` behaves as a `#define name value` line at the
+    /// top of `source`. The entry point of GLSL shaders is always `main`.
+    #[cfg(feature = "glsl")]
+    pub fn from_glsl_string(
+        fw: &Framework,
+        source: String,
+        defines: &[(&str, &str)],
+        name: Option<&str>,
+    ) -> ShaderResult<Self> {
+        let options = naga::front::glsl::Options {
+            stage: naga::ShaderStage::Compute,
+            defines: defines
+                .iter()
+                .map(|&(name, value)| (name.to_owned(), value.to_owned()))
+                .collect(),
+        };
+
+        let module = naga::front::glsl::Frontend::default()
+            .parse(&options, &source)
+            .map_err(|errors| {
+                let diagnoses: Vec<_> = errors
+                    .iter()
+                    .map(|err| {
+                        let message = err.kind.to_string();
+                        let location = err.meta.location(&source);
+
+                        SourceText::Plain(&source).diagnose(
+                            name,
+                            message.clone(),
+                            Some(location),
+                            |text, path| {
+                                let location = SourceLocation {
+                                    file: Some(path.to_owned()),
+                                    ..location.into()
+                                };
+                                let snippet = line_of(text, location.line);
+
+                                render_diagnostic(&message, &location, snippet.as_deref())
+                            },
+                        )
+                    })
+                    .collect();
+
+                let diagnostic = diagnoses
+                    .iter()
+                    .map(|diagnosis| diagnosis.diagnostic.as_str())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                let (location, snippet) = diagnoses
+                    .into_iter()
+                    .next()
+                    .map(|diagnosis| (diagnosis.location, diagnosis.snippet))
+                    .unwrap_or_default();
+
+                ShaderError::ParseError {
+                    label: name.map(str::to_owned),
+                    location,
+                    snippet,
+                    diagnostic,
+                }
+            })?;
+
+        Self::from_module(fw, module, Some(SourceText::Plain(&source)), name)
+    }
+
+    /// Validates `module` against the capabilities of `fw` and creates the [`Shader`] from it.
+    ///
+    /// `source` is the text `module` was parsed from, if any, used to locate validation errors.
+    fn from_module(
+        fw: &Framework,
+        module: naga::Module,
+        source: Option<SourceText<'_>>,
+        name: Option<&str>,
+    ) -> ShaderResult<Self> {
+        naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            capabilities(fw.features(), fw.adapter.get_downlevel_capabilities().flags),
+        )
+        .validate(&module)
+        .map_err(|err| {
+            let diagnosis = match source {
+                Some(source) => source.diagnose(
+                    name,
+                    error_chain(&err),
+                    err.location(source.text()),
+                    |text, path| err.emit_to_string_with_path(text, path),
+                ),
+                None => Diagnosis {
+                    location: None,
+                    snippet: None,
+                    diagnostic: error_chain(&err),
+                },
+            };
+
+            ShaderError::ValidationError {
+                label: name.map(str::to_owned),
+                location: diagnosis.location,
+                snippet: diagnosis.snippet,
+                diagnostic: diagnosis.diagnostic,
+            }
+        })?;
+
+        let shader_module = fw
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
                 label: name,
-                source: wgpu::ShaderSource::Wgsl(Cow::Owned(source)),
-            },
-        )))
+                source: wgpu::ShaderSource::Naga(Cow::Owned(module.clone())),
+            });
+
+        Ok(Self {
+            module: Arc::new(shader_module),
+            reflection: Some(module),
+            label: name.map(str::to_owned),
+            link: None,
+        })
+    }
+
+    /// Returns the reflected [`naga::Module`] of this [`Shader`], if available.
+    pub fn reflection(&self) -> Option<&naga::Module> {
+        self.reflection.as_ref()
+    }
+
+    /// Returns the byte size of the resource bound at `group` and `binding`,
+    /// following the layout rules of the shader, or `None` if there is no such resource.
+    ///
+    /// Runtime-sized arrays account for a single element, as in their minimum binding size.
+    pub fn binding_size(&self, group: u32, binding: u32) -> ShaderResult<Option<u64>> {
+        Ok(self
+            .binding_layout(group, binding)?
+            .map(|(_, var, layouter)| layouter[var.ty].size as u64))
+    }
+
+    /// Returns the reflected resource bound at `group` and `binding`, along with its module
+    /// and the layout of its types.
+    pub(crate) fn binding_layout(
+        &self,
+        group: u32,
+        binding: u32,
+    ) -> ShaderResult<Option<(&naga::Module, &naga::GlobalVariable, naga::proc::Layouter)>> {
+        let module = match &self.reflection {
+            Some(module) => module,
+            None => return Ok(None),
+        };
+        let var = match module.global_variables.iter().find_map(|(_, var)| {
+            var.binding
+                .as_ref()
+                .filter(|res| res.group == group && res.binding == binding)
+                .map(|_| var)
+        }) {
+            Some(var) => var,
+            None => return Ok(None),
+        };
+
+        let mut layouter = naga::proc::Layouter::default();
+        layouter
+            .update(module.to_ctx())
+            .map_err(|err| ShaderError::TypeLayoutError {
+                label: self.label.clone(),
+                diagnostic: error_chain(&err),
+            })?;
+
+        Ok(Some((module, var, layouter)))
     }
 }
 
@@ -274,9 +1174,18 @@ impl<'sha, 'res> Program<'sha, 'res> {
             shader,
             entry_point: entry_point.into(),
             descriptors: Vec::new(),
+            label: None,
         }
     }
 
+    /// Sets the debug label of the [`Kernel`] created from this [`Program`].
+    ///
+    /// Defaults to the `entry_point` name.
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
     /// Adds a [`DescriptorSet`] to this [`Program`] layout.
     pub fn add_descriptor_set(mut self, desc: DescriptorSet<'res>) -> Self {
         self.descriptors.push(desc);
@@ -284,28 +1193,43 @@ impl<'sha, 'res> Program<'sha, 'res> {
     }
 }
 
-impl<'fw> Kernel<'fw> {
+impl Kernel {
     /// Creates a [`Kernel`] from a [`Program`].
-    pub fn new<'sha, 'res>(fw: &'fw Framework, program: Program<'sha, 'res>) -> Self {
+    pub fn new<'sha, 'res>(fw: &Framework, program: Program<'sha, 'res>) -> Self {
+        let label = match program.label {
+            Some(label) => label,
+            None => program.entry_point.clone(),
+        };
+
         let mut layouts = Vec::new();
         let mut sets = Vec::new();
 
         // Unwraping of descriptors from program
         for (set_id, desc) in program.descriptors.iter().enumerate() {
+            let set_label = desc
+                .label
+                .clone()
+                .unwrap_or_else(|| format!("{} set {}", label, set_id));
+
             let set_layout = fw
                 .device
                 .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                    label: None,
+                    label: Some(&set_label),
                     entries: &desc.set_layout,
                 });
 
             let set = fw.device.create_bind_group(&wgpu::BindGroupDescriptor {
-                label: None,
+                label: Some(&set_label),
                 layout: &set_layout,
                 entries: &desc.binds,
             });
 
-            log::debug!("Binding set = {} with {:#?}", set_id, &desc.binds);
+            log::debug!(
+                "Binding set = {} ({}) with {:#?}",
+                set_id,
+                set_label,
+                &desc.binds
+            );
 
             layouts.push(set_layout);
             sets.push(set);
@@ -314,57 +1238,231 @@ impl<'fw> Kernel<'fw> {
         // Compute pipeline bindings
         let group_layouts = layouts.iter().collect::<Vec<_>>();
 
-        let pipeline_layout = fw
+        let layout = fw
             .device
             .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                label: None,
+                label: Some(&label),
                 bind_group_layouts: &group_layouts,
                 push_constant_ranges: &[],
             });
 
-        let pipeline = fw
-            .device
-            .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
-                label: None,
-                module: &program.shader.0,
-                entry_point: &program.entry_point,
-                layout: Some(&pipeline_layout),
-            });
+        let shader = program.shader;
+        let entry_point = program.entry_point;
+        let create = |module: &wgpu::ShaderModule| {
+            let pipeline = create_pipeline(fw, &layout, module, &entry_point, &label);
+
+            KernelPipeline {
+                pipeline: RwLock::new(pipeline),
+                layout,
+                entry_point,
+                label,
+            }
+        };
+
+        // Kernels of watched shaders use their latest version and follow their reloads.
+        let pipeline = match &shader.link {
+            Some(link) => link.follow(create),
+            None => Arc::new(create(&shader.module)),
+        };
 
         Self {
-            fw,
+            fw: fw.clone(),
             pipeline,
             sets,
-            entry_point: program.entry_point,
+            _link: shader.link.clone(),
         }
     }
 
+    /// Returns the debug label of this [`Kernel`].
+    pub fn label(&self) -> &str {
+        &self.pipeline.label
+    }
+
+    /// Replaces the shader of this [`Kernel`] by `shader`, keeping its entry point and bindings.
+    ///
+    /// A watched `shader` is replaced by its latest version. On error, the current shader
+    /// is kept. Enqueues concurrent to this call run either the previous or the new shader.
+    /// Kernels created from a watched shader switch back to it on its next reload.
+    pub fn set_shader(&self, shader: &Shader) -> ShaderResult<()> {
+        let module = match &shader.link {
+            Some(link) => link.module(),
+            None => Arc::clone(&shader.module),
+        };
+
+        let pipeline = self.pipeline.build(&self.fw, shader, &module)?;
+        self.pipeline.set(pipeline);
+
+        Ok(())
+    }
+
     /// Enqueues the execution of this [`Kernel`] onto the GPU.
     ///
     /// [`Kernel`] will dispatch `x`, `y` and `z` workgroups per dimension.
+    /// It can be called concurrently from several threads sharing this [`Kernel`];
+    /// each call submits its own command buffer.
     pub fn enqueue(&self, x: u32, y: u32, z: u32) {
         let mut encoder = self
             .fw
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                label: Some("Kernel::enqueue"),
+                label: Some(self.label()),
             });
+        let pipeline = self.pipeline.current();
         {
             let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
-                label: Some("Kernel::enqueue"),
+                label: Some(self.label()),
                 timestamp_writes: None,
             });
 
-            cpass.set_pipeline(&self.pipeline);
+            cpass.set_pipeline(&pipeline);
 
             for (id_set, set) in self.sets.iter().enumerate() {
                 cpass.set_bind_group(id_set as u32, set, &[]);
             }
 
-            cpass.insert_debug_marker(&self.entry_point);
+            cpass.insert_debug_marker(&self.pipeline.entry_point);
             cpass.dispatch_workgroups(x, y, z);
         }
 
         self.fw.queue.submit(Some(encoder.finish()));
     }
 }
+
+/// Compute pipeline of a [`Kernel`], replaced when its shader changes.
+pub(crate) struct KernelPipeline {
+    pipeline: RwLock<wgpu::ComputePipeline>,
+    layout: wgpu::PipelineLayout,
+    entry_point: String,
+    label: String,
+}
+
+impl KernelPipeline {
+    /// Returns the current pipeline, which is not replaced while the guard lives.
+    fn current(&self) -> std::sync::RwLockReadGuard<'_, wgpu::ComputePipeline> {
+        // A pipeline is always valid, even if a panicking thread held the lock.
+        self.pipeline.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Creates a pipeline running `module`, the module of `shader`.
+    ///
+    /// Errors reported by `wgpu` while creating it, such as a missing entry point or
+    /// bindings incompatible with the kernel layout, are returned instead of being raised
+    /// as device errors.
+    fn build(
+        &self,
+        fw: &Framework,
+        shader: &Shader,
+        module: &wgpu::ShaderModule,
+    ) -> ShaderResult<wgpu::ComputePipeline> {
+        fw.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let pipeline = create_pipeline(fw, &self.layout, module, &self.entry_point, &self.label);
+
+        match fw.block_on(fw.device.pop_error_scope()) {
+            Some(err) => Err(ShaderError::IncompatibleKernel {
+                label: shader.label.clone(),
+                kernel: self.label.clone(),
+                // Its message already lists the causes of the error.
+                diagnostic: err.to_string(),
+            }),
+            None => Ok(pipeline),
+        }
+    }
+
+    /// Replaces the current pipeline by `pipeline`.
+    fn set(&self, pipeline: wgpu::ComputePipeline) {
+        *self
+            .pipeline
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = pipeline;
+    }
+}
+
+fn create_pipeline(
+    fw: &Framework,
+    layout: &wgpu::PipelineLayout,
+    module: &wgpu::ShaderModule,
+    entry_point: &str,
+    label: &str,
+) -> wgpu::ComputePipeline {
+    fw.device
+        .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some(label),
+            module,
+            entry_point,
+            layout: Some(layout),
+        })
+}
+
+/// Link between a watched [`Shader`] and the [`Kernel`]s created from it.
+pub(crate) struct ShaderLink {
+    state: Mutex<LinkState>,
+}
+
+struct LinkState {
+    module: Arc<wgpu::ShaderModule>,
+    kernels: Vec<Weak<KernelPipeline>>,
+}
+
+impl ShaderLink {
+    /// Links `shader` so that the kernels created from it follow [`ShaderLink::update`].
+    pub(crate) fn attach(shader: &mut Shader) -> Arc<Self> {
+        let link = Arc::new(Self {
+            state: Mutex::new(LinkState {
+                module: Arc::clone(&shader.module),
+                kernels: Vec::new(),
+            }),
+        });
+
+        shader.link = Some(Arc::clone(&link));
+        link
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, LinkState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns the latest module of the linked shader.
+    pub(crate) fn module(&self) -> Arc<wgpu::ShaderModule> {
+        Arc::clone(&self.state().module)
+    }
+
+    /// Creates the pipeline of a new kernel from the latest module and registers it.
+    fn follow(
+        &self,
+        create: impl FnOnce(&wgpu::ShaderModule) -> KernelPipeline,
+    ) -> Arc<KernelPipeline> {
+        let mut state = self.state();
+        let pipeline = Arc::new(create(&state.module));
+
+        state.kernels.push(Arc::downgrade(&pipeline));
+        pipeline
+    }
+
+    /// Makes `shader` the latest version, swapping the pipelines of the live kernels.
+    ///
+    /// Returns how many kernels were updated. When `shader` is incompatible with any of them,
+    /// returns the error without updating any, and the previous version stays the latest.
+    pub(crate) fn update(&self, fw: &Framework, shader: &Shader) -> ShaderResult<usize> {
+        let mut state = self.state();
+
+        state.kernels.retain(|kernel| kernel.strong_count() > 0);
+        let kernels = state
+            .kernels
+            .iter()
+            .filter_map(Weak::upgrade)
+            .collect::<Vec<_>>();
+
+        let pipelines = kernels
+            .iter()
+            .map(|kernel| kernel.build(fw, shader, &shader.module))
+            .collect::<ShaderResult<Vec<_>>>()?;
+
+        for (kernel, pipeline) in kernels.iter().zip(pipelines) {
+            kernel.set(pipeline);
+        }
+
+        state.module = Arc::clone(&shader.module);
+
+        Ok(kernels.len())
+    }
+}
@@ -10,7 +10,7 @@
 //! ## Rust program
 //! ```no_run
 //!     use gpgpu::*;
-//! fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! fn main() -> Result<(), Error> {
 //!     // Framework initialization
 //!     let fw = Framework::default();
 //!
@@ -65,24 +65,60 @@
 //! }
 //! ```
 //!
+//! # Errors
+//! Each module returns its own specific error type, all of which convert into [`Error`].
+//! Applications can therefore propagate any `gpgpu` failure with `?` into a single type,
+//! as in the example above. Infallible-looking constructors such as [`GpuBuffer::from_slice`]
+//! panic instead, and have `try_*` counterparts returning the error.
+//!
+//! # Thread safety
+//! [`Framework`], the GPU primitives, [`Sampler`], [`Shader`] and [`Kernel`] are `Send + Sync`
+//! on native targets, so they can be shared between threads (e.g. behind an [`Arc`]).
+//! [`Kernel::enqueue`] only takes `&self` and records its own commands on every call, so a single
+//! [`Kernel`] can be enqueued concurrently from many threads.
+//!
+//! # Async runtimes
+//! Read futures poll the GPU when awaited, then sleep until the [`Framework`] polling thread
+//! completes their download and wakes them, so they make progress on any executor (tokio,
+//! async-std...) without busy-waiting. The `*_blocking` methods wait on the GPU directly
+//! instead of using a general purpose executor.
+//! With the `integrate-tokio` feature, `Framework::spawn_blocking` and `Framework::wait_idle`
+//! offload blocking work to the tokio blocking thread pool.
+//!
 
 use std::{marker::PhantomData, sync::Arc};
 
+pub use error::Error;
+#[cfg(feature = "integrate-encase")]
+pub use features::integrate_encase::{GpuStorageStruct, GpuUniformStruct};
 #[cfg(feature = "integrate-ndarray")]
 pub use features::integrate_ndarray::GpuArray;
 pub use primitives::{BufOps, ImgOps};
 
+pub mod error;
 pub mod features;
 pub mod framework;
 pub mod kernel;
+pub mod memory;
+pub mod preprocessor;
 pub mod primitives;
+pub mod watcher;
 
 /// Entry point of `gpgpu`. A [`Framework`] must be created
 /// first as all GPU primitives needs it to be created.
+///
+/// Cloning a [`Framework`] is cheap: clones share the same GPU device and queue.
+/// GPU primitives keep their own clone of the [`Framework`] they are created with, so they
+/// are `'static` and can be moved into threads, async tasks or long-lived structs.
+#[derive(Clone)]
 pub struct Framework {
+    // Dropped first, so the polling thread is stopped before the device is released.
+    _poller: Arc<framework::Poller>,
     device: Arc<wgpu::Device>,
-    queue: wgpu::Queue,
-    adapter: wgpu::Adapter,
+    queue: Arc<wgpu::Queue>,
+    adapter: Arc<wgpu::Adapter>,
+    memory: Arc<memory::MemoryTracker>,
+    image_kernels: Arc<primitives::images::ImageKernels>,
 }
 
 #[derive(PartialEq, Eq)]
@@ -108,11 +144,13 @@ pub enum GpuBufferUsage {
 ///
 /// More information about its shader representation is
 /// under the [`DescriptorSet::bind_buffer`](crate::DescriptorSet::bind_buffer) documentation.
-pub struct GpuBuffer<'fw, T> {
-    fw: &'fw Framework,
+pub struct GpuBuffer<T> {
+    fw: Framework,
     buf: wgpu::Buffer,
     size: u64,
+    label: Option<String>,
     marker: PhantomData<T>,
+    _allocation: memory::Allocation,
 }
 
 /// Uniform vector of contiguous homogeneous elements on GPU memory.
@@ -123,11 +161,49 @@ pub struct GpuBuffer<'fw, T> {
 ///
 /// More information about its shader representation is
 /// under the [`DescriptorSet::bind_uniform_buffer`](crate::DescriptorSet::bind_uniform_buffer) documentation.
-pub struct GpuUniformBuffer<'fw, T> {
-    fw: &'fw Framework,
+pub struct GpuUniformBuffer<T> {
+    fw: Framework,
     buf: wgpu::Buffer,
     size: u64,
+    label: Option<String>,
     marker: PhantomData<T>,
+    _allocation: memory::Allocation,
+}
+
+/// Buffer made of a header `H` followed by a runtime-sized array of `T` elements.
+/// Both must implement [`bytemuck::Pod`].
+///
+/// Equivalent to a WGSL storage struct whose last member is a runtime-sized array.
+///
+/// More information about its shader representation is
+/// under the [`DescriptorSet::bind_struct_buffer`](crate::DescriptorSet::bind_struct_buffer) documentation.
+pub struct GpuStructBuffer<H, T> {
+    fw: Framework,
+    buf: wgpu::Buffer,
+    capacity: u64,
+    array_offset: u64,
+    label: Option<String>,
+    marker: PhantomData<(H, T)>,
+    _allocation: memory::Allocation,
+}
+
+/// Single `atomic<u32>` counter on GPU memory.
+///
+/// Equivalent to OpenCL's atomic counters.
+///
+/// More information about its shader representation is
+/// under the [`DescriptorSet::bind_atomic_counter`](crate::DescriptorSet::bind_atomic_counter) documentation.
+pub struct GpuAtomicCounter {
+    buf: GpuBuffer<u32>,
+}
+
+/// Append-only array of `T` elements with an atomic element count on GPU memory.
+/// Its elements must implement [`bytemuck::Pod`].
+///
+/// More information about its shader representation is
+/// under the [`DescriptorSet::bind_append_buffer`](crate::DescriptorSet::bind_append_buffer) documentation.
+pub struct GpuAppendBuffer<T> {
+    buf: GpuStructBuffer<u32, T>,
 }
 
 /// 2D-image of homogeneous pixels.
@@ -136,12 +212,14 @@ pub struct GpuUniformBuffer<'fw, T> {
 ///
 /// More information about its shader representation is
 /// under the [`DescriptorSet::bind_image`](crate::DescriptorSet::bind_image) documentation.
-pub struct GpuImage<'fw, P> {
-    fw: &'fw Framework,
+pub struct GpuImage<P> {
+    fw: Framework,
     texture: wgpu::Texture,
     size: wgpu::Extent3d,
     full_view: wgpu::TextureView,
+    label: Option<String>,
     pixel: PhantomData<P>,
+    _allocation: memory::Allocation,
 }
 
 /// 2D-image of homogeneous pixels.
@@ -150,12 +228,80 @@ pub struct GpuImage<'fw, P> {
 ///
 /// More information about its shader representation is
 /// under the [`DescriptorSet::bind_const_image`](crate::DescriptorSet::bind_const_image) documentation.
-pub struct GpuConstImage<'fw, P> {
-    fw: &'fw Framework,
+pub struct GpuConstImage<P> {
+    fw: Framework,
+    texture: wgpu::Texture,
+    size: wgpu::Extent3d,
+    full_view: wgpu::TextureView,
+    label: Option<String>,
+    mip_views: Vec<wgpu::TextureView>,
+    blit_target: std::sync::OnceLock<primitives::images::BlitTarget>,
+    pixel: PhantomData<P>,
+    _allocation: memory::Allocation,
+}
+
+/// 3D-image of homogeneous pixels, made of `depth` 2D slices.
+///
+/// Equivalent to write-only OpenCL's 3D Image objects.
+///
+/// More information about its shader representation is
+/// under the [`DescriptorSet::bind_image_3d`](crate::DescriptorSet::bind_image_3d) documentation.
+pub struct GpuImage3d<P> {
+    fw: Framework,
     texture: wgpu::Texture,
     size: wgpu::Extent3d,
     full_view: wgpu::TextureView,
+    label: Option<String>,
     pixel: PhantomData<P>,
+    _allocation: memory::Allocation,
+}
+
+/// 3D-image of homogeneous pixels, made of `depth` 2D slices.
+///
+/// Equivalent to read-only OpenCL's 3D Image objects.
+///
+/// More information about its shader representation is
+/// under the [`DescriptorSet::bind_const_image_3d`](crate::DescriptorSet::bind_const_image_3d) documentation.
+pub struct GpuConstImage3d<P> {
+    fw: Framework,
+    texture: wgpu::Texture,
+    size: wgpu::Extent3d,
+    full_view: wgpu::TextureView,
+    label: Option<String>,
+    pixel: PhantomData<P>,
+    _allocation: memory::Allocation,
+}
+
+/// Array of 2D-images of homogeneous pixels with the same dimensions.
+///
+/// Equivalent to write-only OpenCL's 2D Image Array objects.
+///
+/// More information about its shader representation is
+/// under the [`DescriptorSet::bind_image_array`](crate::DescriptorSet::bind_image_array) documentation.
+pub struct GpuImageArray<P> {
+    fw: Framework,
+    texture: wgpu::Texture,
+    size: wgpu::Extent3d,
+    full_view: wgpu::TextureView,
+    label: Option<String>,
+    pixel: PhantomData<P>,
+    _allocation: memory::Allocation,
+}
+
+/// Array of 2D-images of homogeneous pixels with the same dimensions.
+///
+/// Equivalent to read-only OpenCL's 2D Image Array objects.
+///
+/// More information about its shader representation is
+/// under the [`DescriptorSet::bind_const_image_array`](crate::DescriptorSet::bind_const_image_array) documentation.
+pub struct GpuConstImageArray<P> {
+    fw: Framework,
+    texture: wgpu::Texture,
+    size: wgpu::Extent3d,
+    full_view: wgpu::TextureView,
+    label: Option<String>,
+    pixel: PhantomData<P>,
+    _allocation: memory::Allocation,
 }
 
 /// The wrapping mode to use for a sampler.
@@ -164,6 +310,7 @@ pub struct GpuConstImage<'fw, P> {
 ///
 /// More information about its shader representation is
 /// under the [`DescriptorSet::bind_sampler`](crate::DescriptorSet::bind_sampler) documentation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum SamplerWrapMode {
     ClampToEdge,
     Repeat,
@@ -177,6 +324,7 @@ pub enum SamplerWrapMode {
 ///
 /// More information about its shader representation is
 /// under the [`DescriptorSet::bind_sampler`](crate::DescriptorSet::bind_sampler) documentation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum SamplerFilterMode {
     Nearest,
     Linear,
@@ -190,19 +338,38 @@ pub enum SamplerFilterMode {
 /// under the [`DescriptorSet::bind_sampler`](crate::DescriptorSet::bind_sampler) documentation.
 pub struct Sampler {
     sampler: wgpu::Sampler,
-    filter_mode: SamplerFilterMode,
+    binding_type: wgpu::SamplerBindingType,
+    label: Option<String>,
 }
 
 /// Represents a shader.
 ///
-/// It's just a wrapper around [`wgpu::ShaderModule`].
-pub struct Shader(wgpu::ShaderModule);
+/// It's a wrapper around [`wgpu::ShaderModule`] that also keeps
+/// the shader [`naga::Module`] for reflection.
+///
+/// Shaders are parsed and validated by `naga` against the capabilities of the [`Framework`]
+/// before being handed to `wgpu`, so invalid sources return a
+/// [`ShaderError`](kernel::ShaderError) instead of panicking.
+///
+/// Shaders can be written in WGSL or SPIR-V, and in GLSL with the `glsl` feature.
+/// WGSL sources split across files can be assembled by a
+/// [`WgslPreprocessor`](preprocessor::WgslPreprocessor).
+///
+/// Shaders loaded through a [`ShaderWatcher`](watcher::ShaderWatcher) are reloaded when their
+/// files change, along with the [`Kernel`]s created from them.
+pub struct Shader {
+    module: Arc<wgpu::ShaderModule>,
+    reflection: Option<naga::Module>,
+    label: Option<String>,
+    link: Option<Arc<kernel::ShaderLink>>,
+}
 
 /// Represents an entry point with its bindings on a [`Shader`].
 pub struct Program<'sha, 'res> {
     shader: &'sha Shader,
     entry_point: String,
     descriptors: Vec<DescriptorSet<'res>>,
+    label: Option<String>,
 }
 
 /// Contains a binding group of resources.
@@ -210,14 +377,52 @@ pub struct Program<'sha, 'res> {
 pub struct DescriptorSet<'res> {
     set_layout: Vec<wgpu::BindGroupLayoutEntry>,
     binds: Vec<wgpu::BindGroupEntry<'res>>,
+    label: Option<String>,
 }
 
 /// Used to enqueue the execution of a shader with the bidings provided.
 ///
-/// Equivalent to OpenCL's Kernel.
-pub struct Kernel<'fw> {
-    fw: &'fw Framework,
-    pipeline: wgpu::ComputePipeline,
+/// Equivalent to OpenCL's Kernel. It is `Send + Sync`, and can be enqueued
+/// concurrently from several threads.
+///
+/// Its shader can be replaced while in use with [`Kernel::set_shader`]: every enqueue
+/// runs either the previous or the new pipeline.
+pub struct Kernel {
+    fw: Framework,
+    pipeline: Arc<kernel::KernelPipeline>,
     sets: Vec<wgpu::BindGroup>,
-    entry_point: String,
+    /// Keeps a watched shader reloaded while its kernels live.
+    _link: Option<Arc<kernel::ShaderLink>>,
 }
+
+// Compile-time checks of the thread safety guarantees documented at the crate root.
+// wgpu types are not `Send + Sync` on WebAssembly.
+#[cfg(not(target_arch = "wasm32"))]
+const _: () = {
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[allow(dead_code)]
+    fn assert_all() {
+        use primitives::pixels::Rgba8Uint;
+
+        assert_send_sync::<Framework>();
+        assert_send_sync::<GpuBuffer<u32>>();
+        assert_send_sync::<GpuUniformBuffer<u32>>();
+        assert_send_sync::<GpuStructBuffer<u32, u32>>();
+        assert_send_sync::<GpuAtomicCounter>();
+        assert_send_sync::<GpuAppendBuffer<u32>>();
+        assert_send_sync::<GpuImage<Rgba8Uint>>();
+        assert_send_sync::<GpuConstImage<Rgba8Uint>>();
+        assert_send_sync::<GpuImage3d<Rgba8Uint>>();
+        assert_send_sync::<GpuConstImage3d<Rgba8Uint>>();
+        assert_send_sync::<GpuImageArray<Rgba8Uint>>();
+        assert_send_sync::<GpuConstImageArray<Rgba8Uint>>();
+        assert_send_sync::<Sampler>();
+        assert_send_sync::<Shader>();
+        assert_send_sync::<Program>();
+        assert_send_sync::<DescriptorSet>();
+        assert_send_sync::<Kernel>();
+        assert_send_sync::<Error>();
+        assert_send_sync::<watcher::ShaderWatcher>();
+    }
+};
@@ -0,0 +1,360 @@
+//! Accounting of the GPU memory allocated through `gpgpu`.
+//!
+//! Every GPU primitive registers its allocation on the [`Framework`] it was created with,
+//! and releases it when dropped. [`Framework::memory_report`] lists the live allocations
+//! and [`Framework::set_memory_budget`] limits them: allocations exceeding the budget
+//! return an [`OutOfBudget`] error instead of reaching the driver. Constructors that
+//! cannot return an error (e.g. [`BufOps::from_slice`](crate::BufOps::from_slice)) panic instead.
+//!
+//! Resources adopted through `from_gpu_parts` are tracked but never rejected,
+//! as they were already allocated.
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use thiserror::Error;
+
+use crate::Framework;
+
+/// Kind of a tracked GPU allocation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MemoryKind {
+    /// Buffers, including uniform, struct and atomic ones.
+    Buffer,
+    /// Images of any dimension, mip levels included.
+    Image,
+    /// Temporary buffers used to move data between the host and the GPU.
+    Staging,
+}
+
+/// Error returned when an allocation would exceed the memory budget of a [`Framework`].
+#[derive(Error, Debug, Clone)]
+#[error(
+    "Allocating {requested} bytes of {kind:?} memory for {} would exceed the memory budget ({used} of {budget} bytes in use).",
+    .label.as_deref().unwrap_or("<unlabelled>")
+)]
+pub struct OutOfBudget {
+    pub label: Option<String>,
+    pub kind: MemoryKind,
+    pub requested: u64,
+    pub used: u64,
+    pub budget: u64,
+}
+
+/// A live GPU allocation listed in a [`MemoryReport`].
+#[derive(Clone, Debug)]
+pub struct ResourceInfo {
+    pub label: Option<String>,
+    pub kind: MemoryKind,
+    pub size: u64,
+}
+
+/// Snapshot of the GPU memory allocated through a [`Framework`] and its clones.
+///
+/// Created by [`Framework::memory_report`].
+#[derive(Clone, Debug)]
+pub struct MemoryReport {
+    /// Live bytes of [`MemoryKind::Buffer`] allocations.
+    pub buffers: u64,
+    /// Live bytes of [`MemoryKind::Image`] allocations.
+    pub images: u64,
+    /// Live bytes of [`MemoryKind::Staging`] allocations.
+    pub staging: u64,
+    /// Highest number of live bytes since the creation of the [`Framework`].
+    pub peak: u64,
+    /// Current memory budget, if any.
+    pub budget: Option<u64>,
+    /// Live allocations, in creation order.
+    pub resources: Vec<ResourceInfo>,
+}
+
+impl MemoryReport {
+    /// Returns the total number of live bytes.
+    pub fn total(&self) -> u64 {
+        self.buffers + self.images + self.staging
+    }
+}
+
+impl fmt::Display for MemoryReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} bytes in use (peak {} bytes", self.total(), self.peak)?;
+        if let Some(budget) = self.budget {
+            write!(f, ", budget {} bytes", budget)?;
+        }
+        writeln!(
+            f,
+            "): {} in buffers, {} in images, {} in staging.",
+            self.buffers, self.images, self.staging
+        )?;
+
+        for resource in &self.resources {
+            writeln!(
+                f,
+                "  {:?} {}: {} bytes",
+                resource.kind,
+                resource.label.as_deref().unwrap_or("<unlabelled>"),
+                resource.size
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct MemoryState {
+    next_id: u64,
+    live: BTreeMap<u64, ResourceInfo>,
+    used: u64,
+    peak: u64,
+    budget: Option<u64>,
+}
+
+/// Shared memory bookkeeping of a [`Framework`] and its clones.
+#[derive(Default)]
+pub(crate) struct MemoryTracker {
+    state: Mutex<MemoryState>,
+}
+
+impl MemoryTracker {
+    fn report(&self) -> MemoryReport {
+        let state = self.state();
+        let live_bytes = |kind| {
+            state
+                .live
+                .values()
+                .filter(|resource| resource.kind == kind)
+                .map(|resource| resource.size)
+                .sum()
+        };
+
+        MemoryReport {
+            buffers: live_bytes(MemoryKind::Buffer),
+            images: live_bytes(MemoryKind::Image),
+            staging: live_bytes(MemoryKind::Staging),
+            peak: state.peak,
+            budget: state.budget,
+            resources: state.live.values().cloned().collect(),
+        }
+    }
+
+    fn allocate(
+        self: &Arc<Self>,
+        kind: MemoryKind,
+        size: u64,
+        label: Option<&str>,
+    ) -> Result<Allocation, OutOfBudget> {
+        let mut state = self.state();
+
+        if let Some(budget) = state.budget {
+            if state
+                .used
+                .checked_add(size)
+                .is_none_or(|used| used > budget)
+            {
+                return Err(OutOfBudget {
+                    label: label.map(str::to_owned),
+                    kind,
+                    requested: size,
+                    used: state.used,
+                    budget,
+                });
+            }
+        }
+
+        Ok(self.register(&mut state, kind, size, label))
+    }
+
+    fn track(self: &Arc<Self>, kind: MemoryKind, size: u64, label: Option<&str>) -> Allocation {
+        let mut state = self.state();
+        self.register(&mut state, kind, size, label)
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, MemoryState> {
+        // The state is always left consistent, even by a panicking thread.
+        self.state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    fn register(
+        self: &Arc<Self>,
+        state: &mut MemoryState,
+        kind: MemoryKind,
+        size: u64,
+        label: Option<&str>,
+    ) -> Allocation {
+        let id = state.next_id;
+        state.next_id += 1;
+        // Adopted resources are never rejected, so the count saturates instead of overflowing.
+        state.used = state.used.saturating_add(size);
+        state.peak = state.peak.max(state.used);
+        state.live.insert(
+            id,
+            ResourceInfo {
+                label: label.map(str::to_owned),
+                kind,
+                size,
+            },
+        );
+
+        Allocation {
+            tracker: Arc::clone(self),
+            id,
+        }
+    }
+}
+
+/// Registration of a live GPU allocation, held by the primitive owning it. Released when dropped.
+pub struct Allocation {
+    tracker: Arc<MemoryTracker>,
+    id: u64,
+}
+
+impl Drop for Allocation {
+    fn drop(&mut self) {
+        let mut state = self.tracker.state();
+
+        if let Some(resource) = state.live.remove(&self.id) {
+            state.used = state.used.saturating_sub(resource.size);
+        }
+    }
+}
+
+/// Returns the number of bytes of `texture`, all its mip levels included.
+pub(crate) fn texture_size(texture: &wgpu::Texture) -> u64 {
+    image_size(
+        texture.format(),
+        texture.size(),
+        texture.dimension(),
+        texture.mip_level_count(),
+    )
+}
+
+/// Returns the number of bytes of an image of `format` and `size`, all its mip levels included.
+pub(crate) fn image_size(
+    format: wgpu::TextureFormat,
+    size: wgpu::Extent3d,
+    dimension: wgpu::TextureDimension,
+    mip_level_count: u32,
+) -> u64 {
+    let (block_width, block_height) = format.block_dimensions();
+    let block_size = format.block_copy_size(None).unwrap_or(0) as u64;
+
+    (0..mip_level_count)
+        .map(|level| {
+            let size = size.mip_level_size(level, dimension).physical_size(format);
+
+            (size.width / block_width) as u64
+                * (size.height / block_height) as u64
+                * size.depth_or_array_layers as u64
+                * block_size
+        })
+        .sum()
+}
+
+impl Framework {
+    /// Returns a [`MemoryReport`] of the GPU memory allocated through this [`Framework`]
+    /// and its clones, listing the live resources by label.
+    pub fn memory_report(&self) -> MemoryReport {
+        self.memory.report()
+    }
+
+    /// Sets the maximum number of live bytes allocated through this [`Framework`]
+    /// and its clones, or removes the limit with `None`.
+    ///
+    /// Already live allocations are never released, even if they exceed the new budget.
+    pub fn set_memory_budget(&self, budget: Option<u64>) {
+        self.memory.state().budget = budget;
+    }
+
+    /// Registers a new allocation of `size` bytes, checking it against the budget.
+    pub(crate) fn allocate(
+        &self,
+        kind: MemoryKind,
+        size: u64,
+        label: Option<&str>,
+    ) -> Result<Allocation, OutOfBudget> {
+        self.memory.allocate(kind, size, label)
+    }
+
+    /// Registers an existing allocation of `size` bytes, regardless of the budget.
+    pub(crate) fn track(&self, kind: MemoryKind, size: u64, label: Option<&str>) -> Allocation {
+        self.memory.track(kind, size, label)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker(budget: Option<u64>) -> Arc<MemoryTracker> {
+        let tracker = Arc::new(MemoryTracker::default());
+        tracker.state().budget = budget;
+        tracker
+    }
+
+    #[test]
+    fn allocations_are_released_when_dropped() {
+        let tracker = tracker(None);
+
+        let buffer = tracker.allocate(MemoryKind::Buffer, 64, Some("buffer"));
+        let image = tracker.allocate(MemoryKind::Image, 256, None);
+        let report = tracker.report();
+        assert_eq!(
+            (report.buffers, report.images, report.staging),
+            (64, 256, 0)
+        );
+        assert_eq!(report.resources.len(), 2);
+        assert_eq!(report.resources[0].label.as_deref(), Some("buffer"));
+
+        drop(buffer);
+        drop(image);
+        let report = tracker.report();
+        assert_eq!(report.total(), 0);
+        assert_eq!(report.peak, 320);
+        assert!(report.resources.is_empty());
+    }
+
+    #[test]
+    fn allocations_over_budget_are_rejected() {
+        let tracker = tracker(Some(100));
+
+        let first = tracker.allocate(MemoryKind::Buffer, 60, None).unwrap();
+        let err = tracker
+            .allocate(MemoryKind::Image, 60, Some("image"))
+            .err()
+            .unwrap();
+        assert_eq!((err.requested, err.used, err.budget), (60, 60, 100));
+        assert_eq!(err.label.as_deref(), Some("image"));
+
+        drop(first);
+        assert!(tracker.allocate(MemoryKind::Image, 100, None).is_ok());
+    }
+
+    #[test]
+    fn overflowing_allocations_are_rejected() {
+        let tracker = tracker(Some(u64::MAX));
+
+        let _first = tracker.allocate(MemoryKind::Buffer, 16, None).unwrap();
+        assert!(tracker
+            .allocate(MemoryKind::Buffer, u64::MAX, None)
+            .is_err());
+        assert_eq!(tracker.report().total(), 16);
+    }
+
+    #[test]
+    fn tracked_resources_ignore_the_budget() {
+        let tracker = tracker(Some(10));
+
+        let tracked = tracker.track(MemoryKind::Image, 100, None);
+        assert_eq!(tracker.report().images, 100);
+        assert!(tracker.allocate(MemoryKind::Staging, 1, None).is_err());
+
+        drop(tracked);
+        assert!(tracker.allocate(MemoryKind::Staging, 1, None).is_ok());
+    }
+}
@@ -0,0 +1,729 @@
+//! Preprocessing of `WGSL` shaders split across several files.
+//!
+//! [`WgslPreprocessor`] expands the following directives, which must start their line:
+//! - `#include "path"`: inserts the processed contents of `path`, resolved relative to the
+//!   including file. Virtual files registered with [`WgslPreprocessor::virtual_file`]
+//!   take precedence over the filesystem. Every file is only included once: further
+//!   `#include`s of it are ignored.
+//! - `#define NAME [value]` and `#undef NAME`: (un)defines `NAME`. Defines with a value
+//!   replace every following `NAME` identifier of the code by `value`.
+//! - `#ifdef NAME`, `#ifndef NAME`, `#else` and `#endif`: keeps or drops the enclosed
+//!   lines depending on whether `NAME` is defined.
+//!
+//! The resulting [`ProcessedSource`] remembers where every line comes from, so the errors of
+//! shaders created by [`Shader::from_processed_wgsl`](crate::Shader::from_processed_wgsl)
+//! point at the original file, line and column.
+//!
+//! ```no_run
+//! # use gpgpu::{preprocessor::WgslPreprocessor, Framework, Shader};
+//! # fn main() -> Result<(), gpgpu::Error> {
+//! let fw = Framework::default();
+//! let processed = WgslPreprocessor::new()
+//!     .define("WORKGROUP_SIZE", "64")
+//!     .virtual_file("common.wgsl", "fn square(x: f32) -> f32 { return x * x; }")
+//!     .process_file("shaders/main.wgsl")?;
+//!
+//! let shader = Shader::from_processed_wgsl(&fw, &processed)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::kernel::{line_of, render_diagnostic, ShaderError, ShaderResult, SourceLocation};
+
+/// Expands `#include`, `#define` and `#ifdef` directives of `WGSL` sources.
+#[derive(Clone, Debug, Default)]
+pub struct WgslPreprocessor {
+    defines: BTreeMap<String, String>,
+    virtual_files: BTreeMap<PathBuf, String>,
+}
+
+/// `WGSL` source produced by a [`WgslPreprocessor`], along with the origin of its lines.
+#[derive(Clone, Debug)]
+pub struct ProcessedSource {
+    name: Option<String>,
+    source: String,
+    files: Vec<SourceFile>,
+    lines: Vec<LineOrigin>,
+}
+
+/// A file read while preprocessing.
+#[derive(Clone, Debug)]
+struct SourceFile {
+    path: PathBuf,
+    contents: String,
+    is_virtual: bool,
+}
+
+/// Original file and line of a processed line.
+#[derive(Clone, Debug)]
+struct LineOrigin {
+    file: usize,
+    line: u32,
+    substitutions: Vec<Substitution>,
+}
+
+/// Character columns of a define replaced in a line, in the processed and the original line.
+#[derive(Clone, Debug)]
+struct Substitution {
+    processed: (u32, u32),
+    original: (u32, u32),
+}
+
+/// Conditional block opened by `#ifdef` or `#ifndef`.
+struct Conditional {
+    line: u32,
+    parent_active: bool,
+    active: bool,
+    has_else: bool,
+}
+
+impl WgslPreprocessor {
+    /// Creates a [`WgslPreprocessor`] without defines nor virtual files.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Defines `name` as `value` before processing, as a `#define name value` line would.
+    ///
+    /// Use an empty `value` to only define `name` for `#ifdef`.
+    pub fn define(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.defines.insert(name.into(), value.into());
+        self
+    }
+
+    /// Registers `source` as the contents of `path` for `#include` directives,
+    /// taking precedence over the filesystem.
+    pub fn virtual_file(mut self, path: impl AsRef<Path>, source: impl Into<String>) -> Self {
+        self.virtual_files
+            .insert(normalize(path.as_ref()), source.into());
+        self
+    }
+
+    /// Processes the `WGSL` file at `path`, which may also be a virtual file.
+    pub fn process_file(&self, path: impl AsRef<Path>) -> ShaderResult<ProcessedSource> {
+        let path = normalize(path.as_ref());
+        let file = match self.virtual_files.get(&path) {
+            Some(contents) => SourceFile {
+                path,
+                contents: contents.clone(),
+                is_virtual: true,
+            },
+            None => SourceFile {
+                contents: std::fs::read_to_string(&path)?,
+                path,
+                is_virtual: false,
+            },
+        };
+        let name = file.path.to_str().map(str::to_owned);
+
+        self.process(file, name)
+    }
+
+    /// Processes a `WGSL` `source` with an optional `name`.
+    ///
+    /// Includes of `source` are resolved relative to the directory of `name`,
+    /// or to the current directory without `name`.
+    pub fn process_string(
+        &self,
+        source: impl Into<String>,
+        name: Option<&str>,
+    ) -> ShaderResult<ProcessedSource> {
+        let file = SourceFile {
+            path: PathBuf::from(name.unwrap_or("<string>")),
+            contents: source.into(),
+            is_virtual: true,
+        };
+
+        self.process(file, name.map(str::to_owned))
+    }
+
+    fn process(&self, file: SourceFile, name: Option<String>) -> ShaderResult<ProcessedSource> {
+        let mut processed = ProcessedSource {
+            name,
+            source: String::new(),
+            files: vec![file],
+            lines: Vec::new(),
+        };
+        let mut defines = self.defines.clone();
+
+        self.expand(&mut processed, &mut defines, 0, &mut vec![0])?;
+
+        Ok(processed)
+    }
+
+    /// Appends the processed lines of `files[file]` to `processed`.
+    ///
+    /// `stack` holds the files being included, to detect include cycles.
+    fn expand(
+        &self,
+        processed: &mut ProcessedSource,
+        defines: &mut BTreeMap<String, String>,
+        file: usize,
+        stack: &mut Vec<usize>,
+    ) -> ShaderResult<()> {
+        let contents = processed.files[file].contents.clone();
+        let path = processed.files[file].path.clone();
+        let label = processed.name.clone();
+        let mut conditionals: Vec<Conditional> = Vec::new();
+
+        for (index, text) in contents.lines().enumerate() {
+            let line = index as u32 + 1;
+            let active = conditionals.last().is_none_or(|cond| cond.active);
+            let error = |message: String| preprocess_error(&label, &path, line, text, message);
+
+            let directive = match text.trim_start().strip_prefix('#') {
+                Some(directive) => directive.trim(),
+                None => {
+                    if active {
+                        processed.push_line(file, line, text, defines);
+                    }
+                    continue;
+                }
+            };
+
+            let (keyword, argument) = match directive.find(char::is_whitespace) {
+                Some(end) => (&directive[..end], directive[end..].trim()),
+                None => (directive, ""),
+            };
+
+            match keyword {
+                "ifdef" | "ifndef" => {
+                    let name = identifier(keyword, argument).map_err(error)?;
+                    let defined = defines.contains_key(name);
+
+                    conditionals.push(Conditional {
+                        line,
+                        parent_active: active,
+                        active: active && defined == (keyword == "ifdef"),
+                        has_else: false,
+                    });
+                }
+                "else" => match conditionals.last_mut() {
+                    Some(cond) if !cond.has_else => {
+                        cond.has_else = true;
+                        cond.active = cond.parent_active && !cond.active;
+                    }
+                    Some(_) => return Err(error("Duplicated `#else`.".to_owned())),
+                    None => return Err(error("`#else` without `#ifdef` or `#ifndef`.".to_owned())),
+                },
+                "endif" => {
+                    if conditionals.pop().is_none() {
+                        return Err(error("`#endif` without `#ifdef` or `#ifndef`.".to_owned()));
+                    }
+                }
+                _ if !active => {}
+                "define" => {
+                    let (name, value) = match argument.find(char::is_whitespace) {
+                        Some(end) => (&argument[..end], argument[end..].trim()),
+                        None => (argument, ""),
+                    };
+                    let name = identifier(keyword, name).map_err(error)?;
+
+                    defines.insert(name.to_owned(), value.to_owned());
+                }
+                "undef" => {
+                    let name = identifier(keyword, argument).map_err(error)?;
+                    defines.remove(name);
+                }
+                "include" => {
+                    let path = argument
+                        .strip_prefix('"')
+                        .and_then(|path| path.strip_suffix('"'))
+                        .ok_or_else(|| {
+                            error("Expected a quoted path after `#include`.".to_owned())
+                        })?;
+                    let included = self.resolve(&processed.files[file], path).map_err(error)?;
+
+                    let index = match processed
+                        .files
+                        .iter()
+                        .position(|file| file.path == included.path)
+                    {
+                        Some(index) if stack.contains(&index) => {
+                            return Err(error(format!(
+                                "`#include \"{}\"` forms an include cycle.",
+                                path
+                            )));
+                        }
+                        // Files are included once, so helpers can be shared by several includes.
+                        Some(_) => continue,
+                        None => {
+                            processed.files.push(included);
+                            processed.files.len() - 1
+                        }
+                    };
+
+                    stack.push(index);
+                    self.expand(processed, defines, index, stack)?;
+                    stack.pop();
+                }
+                _ => return Err(error(format!("Unknown directive `#{}`.", keyword))),
+            }
+        }
+
+        match conditionals.last() {
+            Some(cond) => {
+                let text = line_of(&contents, cond.line).unwrap_or_default();
+                Err(preprocess_error(
+                    &label,
+                    &path,
+                    cond.line,
+                    &text,
+                    "Unterminated conditional block, missing `#endif`.".to_owned(),
+                ))
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Resolves the `path` of an `#include` of `includer`.
+    fn resolve(&self, includer: &SourceFile, path: &str) -> Result<SourceFile, String> {
+        let directory = includer.path.parent().unwrap_or_else(|| Path::new(""));
+        let path = normalize(&directory.join(path));
+
+        if let Some(contents) = self.virtual_files.get(&path) {
+            return Ok(SourceFile {
+                path,
+                contents: contents.clone(),
+                is_virtual: true,
+            });
+        }
+
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => Ok(SourceFile {
+                path,
+                contents,
+                is_virtual: false,
+            }),
+            Err(err) => Err(format!("Cannot include `{}`: {}.", path.display(), err)),
+        }
+    }
+}
+
+impl ProcessedSource {
+    /// Returns the name of the processed source, if any.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns the processed `WGSL` source.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Returns the paths of the files on the filesystem that were read while processing,
+    /// `#include`d ones included.
+    pub fn files(&self) -> impl Iterator<Item = &Path> + '_ {
+        self.files
+            .iter()
+            .filter(|file| !file.is_virtual)
+            .map(|file| file.path.as_path())
+    }
+
+    /// Maps a `location` of the processed source to its original file, line and column.
+    pub(crate) fn locate(&self, location: naga::SourceLocation) -> SourceLocation {
+        let origin = match location
+            .line_number
+            .checked_sub(1)
+            .and_then(|index| self.lines.get(index as usize))
+        {
+            Some(origin) => origin,
+            None => {
+                return SourceLocation {
+                    file: self.name.clone(),
+                    line: location.line_number,
+                    column: location.line_position,
+                }
+            }
+        };
+
+        // Shifts the column by the length difference of the defines substituted before it.
+        let column = location.line_position.saturating_sub(1);
+        let mut shift = 0i64;
+        let mut original = None;
+
+        for sub in &origin.substitutions {
+            if column >= sub.processed.1 {
+                shift += (sub.original.1 - sub.original.0) as i64
+                    - (sub.processed.1 - sub.processed.0) as i64;
+            } else if column >= sub.processed.0 {
+                original = Some(sub.original.0);
+                break;
+            } else {
+                break;
+            }
+        }
+
+        SourceLocation {
+            file: self.files[origin.file].path.to_str().map(str::to_owned),
+            line: origin.line,
+            column: original.unwrap_or((column as i64 + shift) as u32) + 1,
+        }
+    }
+
+    /// Returns the original line of a `location` given by [`ProcessedSource::locate`].
+    pub(crate) fn original_line(&self, location: &SourceLocation) -> Option<String> {
+        let file = self
+            .files
+            .iter()
+            .find(|file| file.path.to_str() == location.file.as_deref())?;
+
+        line_of(&file.contents, location.line)
+    }
+
+    /// Appends the `line` of `files[file]` with `text`, substituting `defines`.
+    fn push_line(
+        &mut self,
+        file: usize,
+        line: u32,
+        text: &str,
+        defines: &BTreeMap<String, String>,
+    ) {
+        let mut substitutions = Vec::new();
+        let mut output = String::with_capacity(text.len());
+        let mut chars = text.char_indices().peekable();
+
+        while let Some((start, c)) = chars.next() {
+            if text[start..].starts_with("//") {
+                output.push_str(&text[start..]);
+                break;
+            }
+
+            if !(c.is_alphanumeric() || c == '_') {
+                output.push(c);
+                continue;
+            }
+
+            let mut end = start + c.len_utf8();
+            while let Some(&(index, c)) = chars.peek() {
+                if !(c.is_alphanumeric() || c == '_') {
+                    break;
+                }
+                end = index + c.len_utf8();
+                chars.next();
+            }
+
+            // Numeric literals (e.g. `1e5`) are never substituted.
+            let word = &text[start..end];
+            match defines.get(word).filter(|value| !value.is_empty()) {
+                Some(value) if !c.is_ascii_digit() => {
+                    let processed_start = output.chars().count() as u32;
+                    let original_start = text[..start].chars().count() as u32;
+                    output.push_str(value);
+
+                    substitutions.push(Substitution {
+                        processed: (processed_start, output.chars().count() as u32),
+                        original: (original_start, original_start + word.chars().count() as u32),
+                    });
+                }
+                _ => output.push_str(word),
+            }
+        }
+
+        self.source.push_str(&output);
+        self.source.push('\n');
+        self.lines.push(LineOrigin {
+            file,
+            line,
+            substitutions,
+        });
+    }
+}
+
+/// Creates a [`ShaderError::PreprocessError`] of shader `label` at the `line` of `path` with `text`.
+fn preprocess_error(
+    label: &Option<String>,
+    path: &Path,
+    line: u32,
+    text: &str,
+    message: String,
+) -> ShaderError {
+    let location = SourceLocation {
+        file: path.to_str().map(str::to_owned),
+        line,
+        column: (text.chars().count() - text.trim_start().chars().count()) as u32 + 1,
+    };
+
+    ShaderError::PreprocessError {
+        label: label.clone(),
+        diagnostic: render_diagnostic(&message, &location, Some(text)),
+        snippet: Some(text.to_owned()),
+        location: Some(location),
+    }
+}
+
+/// Returns the identifier `argument` of a `#keyword` directive.
+fn identifier<'a>(keyword: &str, argument: &'a str) -> Result<&'a str, String> {
+    let valid = argument
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_')
+        && argument.chars().all(|c| c.is_alphanumeric() || c == '_');
+
+    if valid {
+        Ok(argument)
+    } else {
+        Err(format!("Expected an identifier after `#{}`.", keyword))
+    }
+}
+
+/// Lexically normalizes `path`, resolving `.` and `..` components.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if normalized.file_name().is_some() {
+                    normalized.pop();
+                } else {
+                    normalized.push("..");
+                }
+            }
+            component => normalized.push(component),
+        }
+    }
+
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn location(line: u32, column: u32) -> naga::SourceLocation {
+        naga::SourceLocation {
+            line_number: line,
+            line_position: column,
+            offset: 0,
+            length: 1,
+        }
+    }
+
+    fn error_location(err: ShaderError) -> SourceLocation {
+        match err {
+            ShaderError::PreprocessError {
+                location: Some(location),
+                ..
+            } => location,
+            err => panic!("Unexpected error: {}", err),
+        }
+    }
+
+    #[test]
+    fn nested_includes() {
+        let processed = WgslPreprocessor::new()
+            .virtual_file("shaders/a.wgsl", "#include \"lib/b.wgsl\"\nfn a() {}")
+            .virtual_file("shaders/lib/b.wgsl", "fn b() {}")
+            .process_string(
+                "#include \"a.wgsl\"\nfn main() {}",
+                Some("shaders/main.wgsl"),
+            )
+            .unwrap();
+
+        assert_eq!(processed.source(), "fn b() {}\nfn a() {}\nfn main() {}\n");
+        assert_eq!(processed.files().count(), 0);
+
+        let b = processed.locate(location(1, 4));
+        assert_eq!(b.file.as_deref(), Some("shaders/lib/b.wgsl"));
+        assert_eq!((b.line, b.column), (1, 4));
+
+        let main = processed.locate(location(3, 4));
+        assert_eq!(main.file.as_deref(), Some("shaders/main.wgsl"));
+        assert_eq!((main.line, main.column), (2, 4));
+    }
+
+    #[test]
+    fn diamond_includes_are_included_once() {
+        let processed = WgslPreprocessor::new()
+            .virtual_file("a.wgsl", "#include \"c.wgsl\"\nfn a() { c(); }")
+            .virtual_file("b.wgsl", "#include \"c.wgsl\"\nfn b() { c(); }")
+            .virtual_file("c.wgsl", "fn c() {}")
+            .process_string("#include \"a.wgsl\"\n#include \"b.wgsl\"", None)
+            .unwrap();
+
+        assert_eq!(
+            processed.source(),
+            "fn c() {}\nfn a() { c(); }\nfn b() { c(); }\n"
+        );
+
+        let b = processed.locate(location(3, 1));
+        assert_eq!(b.file.as_deref(), Some("b.wgsl"));
+        assert_eq!(b.line, 2);
+    }
+
+    #[test]
+    fn include_cycles_are_errors() {
+        let err = WgslPreprocessor::new()
+            .virtual_file("a.wgsl", "#include \"b.wgsl\"")
+            .virtual_file("b.wgsl", "fn b() {}\n  #include \"a.wgsl\"")
+            .process_file("a.wgsl")
+            .unwrap_err();
+
+        assert!(err.to_string().contains("include cycle"));
+
+        let location = error_location(err);
+        assert_eq!(location.file.as_deref(), Some("b.wgsl"));
+        assert_eq!((location.line, location.column), (2, 3));
+    }
+
+    #[test]
+    fn self_includes_are_errors() {
+        let err = WgslPreprocessor::new()
+            .virtual_file("a.wgsl", "#include \"a.wgsl\"")
+            .process_file("a.wgsl")
+            .unwrap_err();
+
+        assert!(err.to_string().contains("include cycle"));
+    }
+
+    #[test]
+    fn missing_includes_are_errors() {
+        let err = WgslPreprocessor::new()
+            .process_string("\n#include \"missing.wgsl\"", Some("main.wgsl"))
+            .unwrap_err();
+
+        let location = error_location(err);
+        assert_eq!(location.file.as_deref(), Some("main.wgsl"));
+        assert_eq!(location.line, 2);
+    }
+
+    #[test]
+    fn nested_conditionals() {
+        let source = "\
+#ifdef A
+a
+#ifndef B
+not_b
+#else
+b
+#endif
+#else
+not_a
+#ifdef B
+not_a_but_b
+#endif
+#endif";
+
+        let process = |preprocessor: WgslPreprocessor| {
+            preprocessor
+                .process_string(source, None)
+                .unwrap()
+                .source()
+                .to_owned()
+        };
+
+        assert_eq!(process(WgslPreprocessor::new()), "not_a\n");
+        assert_eq!(
+            process(WgslPreprocessor::new().define("A", "")),
+            "a\nnot_b\n"
+        );
+        assert_eq!(
+            process(WgslPreprocessor::new().define("A", "").define("B", "")),
+            "a\nb\n"
+        );
+        assert_eq!(
+            process(WgslPreprocessor::new().define("B", "")),
+            "not_a\nnot_a_but_b\n"
+        );
+    }
+
+    #[test]
+    fn conditionals_skip_inactive_directives() {
+        let processed = WgslPreprocessor::new()
+            .process_string(
+                "#ifdef A\n#include \"missing.wgsl\"\n#define B\n#endif\n#ifdef B\nb\n#endif",
+                None,
+            )
+            .unwrap();
+
+        assert_eq!(processed.source(), "");
+    }
+
+    #[test]
+    fn unbalanced_conditionals_are_errors() {
+        let unterminated = WgslPreprocessor::new()
+            .process_string("fn main() {}\n#ifdef A\nfn a() {}", None)
+            .unwrap_err();
+        assert_eq!(error_location(unterminated).line, 2);
+
+        for source in ["#endif", "#else", "#ifdef A\n#else\n#else\n#endif"] {
+            WgslPreprocessor::new()
+                .process_string(source, None)
+                .unwrap_err();
+        }
+    }
+
+    #[test]
+    fn unknown_directives_are_errors() {
+        let err = WgslPreprocessor::new()
+            .process_string("#pragma once", None)
+            .unwrap_err();
+
+        assert!(err.to_string().contains("Unknown directive `#pragma`"));
+    }
+
+    #[test]
+    fn define_substitution() {
+        let processed = WgslPreprocessor::new()
+            .define("SIZE", "64")
+            .process_string(
+                "#define TYPE vec4<f32>\n\
+                 var<private> a: array<TYPE, SIZE>; // TYPE\n\
+                 let b = SIZE_2 + 1e5;\n\
+                 #undef SIZE\n\
+                 let c = SIZE;",
+                None,
+            )
+            .unwrap();
+
+        assert_eq!(
+            processed.source(),
+            "var<private> a: array<vec4<f32>, 64>; // TYPE\n\
+             let b = SIZE_2 + 1e5;\n\
+             let c = SIZE;\n"
+        );
+    }
+
+    #[test]
+    fn locate_after_substitutions() {
+        let processed = WgslPreprocessor::new()
+            .define("LONG_NAME", "1")
+            .define("X", "value")
+            .process_string("let a = LONG_NAME + X + é;", Some("main.wgsl"))
+            .unwrap();
+
+        assert_eq!(processed.source(), "let a = 1 + value + é;\n");
+
+        let column = |processed_column| processed.locate(location(1, processed_column)).column;
+
+        // Before any substitution.
+        assert_eq!(column(5), 5);
+        // Inside substitutions, mapped to their start.
+        assert_eq!(column(9), 9);
+        assert_eq!(column(13), 21);
+        assert_eq!(column(17), 21);
+        // After substitutions, shifted by their length difference.
+        assert_eq!(column(11), 19);
+        assert_eq!(column(21), 25);
+        // Columns are counted in characters.
+        assert_eq!(column(22), 26);
+    }
+
+    #[test]
+    fn locate_outside_of_the_source() {
+        let processed = WgslPreprocessor::new()
+            .process_string("fn main() {}", Some("main.wgsl"))
+            .unwrap();
+
+        let location = processed.locate(location(10, 3));
+        assert_eq!(location.file.as_deref(), Some("main.wgsl"));
+        assert_eq!((location.line, location.column), (10, 3));
+    }
+}
@@ -9,21 +9,48 @@
 //! Intended for small, read-only (in the shader)
 //! chunks of data on the GPU.
 //!
+//! ## GpuStructBuffer
+//! Intended for read-only or read-write (in the shader) structs
+//! made of a fixed header followed by a runtime-sized array.
+//!
+//! ## GpuAtomicCounter and GpuAppendBuffer
+//! Intended for `atomic<u32>` counters and append-only arrays
+//! (an atomic element count followed by its elements) on the GPU.
+//!
 //! # Images
 //! ## GpuImage
-//! Intended for write-only (in the shader) images on the GPU.
+//! Intended for write-only or read-write (in the shader) images on the GPU.
+//! They can also be bound as textures to be read in a later pass, or copied
+//! (and format-converted) into a [`GpuConstImage`](crate::GpuConstImage) on the GPU.
 //!
 //! ## GpuConstImage
 //! Intended for read-only (in th shader) images on the GPU.
+//!
+//! ## GpuImage3d, GpuImageArray and their const variants
+//! Same as above, but made of several depth slices (3D images) or
+//! layers (2D image arrays) that can be read and written one at a time.
+//!
+//! Images can be read and written either as raw bytes or as typed pixels
+//! ([`PixelInfo::Pixel`]), e.g. `[f32; 4]` for [`pixels::Rgba32Float`].
+
+use std::future::Future;
 
 use crate::Framework;
 
+use self::{
+    buffers::{BufferError, BufferResult},
+    images::ImageInputError,
+};
+
+pub mod atomics;
 pub mod buffers;
 pub mod images;
+pub mod layered_images;
 pub mod samplers;
+pub mod struct_buffers;
 
-/// Interface to get information, create and decompose GPU allocated buffers.
-pub trait BufOps<'fw, T>
+/// Interface to get information, create, read and decompose GPU allocated buffers.
+pub trait BufOps<T>
 where
     T: bytemuck::Pod,
 {
@@ -38,24 +65,137 @@ where
     fn size(&self) -> u64;
 
     /// Returns a [`wgpu::BindingResource`] of all the elements in the buffer.
-    fn as_binding_resource(&self) -> wgpu::BindingResource {
+    fn as_binding_resource(&self) -> wgpu::BindingResource<'_> {
         self.as_gpu_buffer().as_entire_binding()
     }
 
     /// Returns the [`wgpu::Buffer`] that handles the GPU data of the buffer.
     fn as_gpu_buffer(&self) -> &wgpu::Buffer;
 
+    /// Returns the [`Framework`] the buffer was created with.
+    fn framework(&self) -> &Framework;
+
+    /// Returns the debug label of the buffer, if any.
+    fn label(&self) -> Option<&str>;
+
+    /// Returns `true` if the buffer can be read back from the GPU,
+    /// that is, if it was created with [`wgpu::BufferUsages::COPY_SRC`].
+    fn is_readable(&self) -> bool {
+        self.as_gpu_buffer()
+            .usage()
+            .contains(wgpu::BufferUsages::COPY_SRC)
+    }
+
+    // ----------- Read fns ------------------
+
+    /// Pulls some elements from the buffer into `buf`, returning how many elements were read.
+    ///
+    /// Fails with [`BufferError::NotReadable`] if the buffer is not [`BufOps::is_readable`].
+    /// The returned future is `Send`, so it can be spawned on multi-threaded runtimes.
+    fn read(&self, buf: &mut [T]) -> impl Future<Output = BufferResult<u64>> + Send
+    where
+        Self: Sync,
+        T: Send,
+    {
+        async move {
+            if !self.is_readable() {
+                return Err(BufferError::NotReadable {
+                    label: self.label().map(str::to_owned),
+                });
+            }
+
+            let output_size = std::mem::size_of_val(buf) as u64;
+            let download_size = if output_size > self.size() {
+                self.size()
+            } else {
+                output_size
+            };
+
+            if download_size == 0 {
+                return Ok(0);
+            }
+
+            // Copies between buffers must be `wgpu::COPY_BUFFER_ALIGNMENT` sized.
+            let gpu_buffer = self.as_gpu_buffer();
+            let copy_size = wgpu::util::align_to(download_size, wgpu::COPY_BUFFER_ALIGNMENT)
+                .min(gpu_buffer.size());
+
+            let download =
+                buffers::download_buffer(self.framework(), gpu_buffer, 0..copy_size, self.label())
+                    .await?;
+
+            let elements = (download_size / std::mem::size_of::<T>() as u64) as usize;
+            let bytes = elements * std::mem::size_of::<T>();
+            buf[..elements].copy_from_slice(bytemuck::cast_slice(&download[..bytes]));
+
+            Ok(elements as u64)
+        }
+    }
+
+    /// Pulls all the elements from the buffer into a [`Vec`].
+    fn read_vec(&self) -> impl Future<Output = BufferResult<Vec<T>>> + Send
+    where
+        Self: Sync,
+        T: Send,
+    {
+        async move {
+            let mut buf = vec![T::zeroed(); self.capacity() as usize];
+            self.read(&mut buf).await?;
+
+            Ok(buf)
+        }
+    }
+
+    /// Blocking version of [`BufOps::read`].
+    fn read_blocking(&self, buf: &mut [T]) -> BufferResult<u64>
+    where
+        Self: Sync,
+        T: Send,
+    {
+        self.framework().block_on(self.read(buf))
+    }
+
+    /// Blocking version of [`BufOps::read_vec`].
+    fn read_vec_blocking(&self) -> BufferResult<Vec<T>>
+    where
+        Self: Sync,
+        T: Send,
+    {
+        self.framework().block_on(self.read_vec())
+    }
+
     // ----------- Creation fns --------------
 
     /// Constructs a new zeroed buffer with the specified capacity.
     ///
-    /// The buffer will be able to hold exactly `capacity` elements.
-    fn with_capacity(fw: &'fw Framework, capacity: u64) -> Self;
+    /// The buffer will be able to hold exactly `capacity` elements. It has no debug label,
+    /// which can be set by creating it with the `builder` of its type instead.
+    ///
+    /// # Panics
+    /// If the buffer exceeds the limits of the device or the memory budget.
+    /// See [`BufOps::try_with_capacity`].
+    fn with_capacity(fw: &Framework, capacity: u64) -> Self;
+
+    /// Fallible version of [`BufOps::with_capacity`], checking the buffer size against
+    /// the `max_buffer_size` limit of the device and the memory budget.
+    fn try_with_capacity(fw: &Framework, capacity: u64) -> BufferResult<Self>
+    where
+        Self: Sized;
 
     /// Constructs a new buffer from a slice.
     ///
     /// The buffer `capacity` will be the `slice` length.
-    fn from_slice(fw: &'fw Framework, slice: &[T]) -> Self;
+    ///
+    /// # Panics
+    /// If the buffer exceeds the limits of the device or the memory budget.
+    /// See [`BufOps::try_from_slice`].
+    fn from_slice(fw: &Framework, slice: &[T]) -> Self;
+
+    /// Fallible version of [`BufOps::from_slice`], checking the buffer size against
+    /// the `max_buffer_size` limit of the device and the memory budget.
+    fn try_from_slice(fw: &Framework, slice: &[T]) -> BufferResult<Self>
+    where
+        Self: Sized;
 
     /// Constructs a new buffer from a [`wgpu::Buffer`] and its byte `size`.
     ///
@@ -64,7 +204,25 @@ where
     /// panic at any time during its usage.
     /// - `size` needs to be less than or equal to the `buf` creation size.
     /// - `size` needs to be multiple of the `T` size.
-    fn from_gpu_parts(fw: &'fw Framework, buf: wgpu::Buffer, size: u64) -> Self;
+    fn from_gpu_parts(fw: &Framework, buf: wgpu::Buffer, size: u64) -> Self
+    where
+        Self: Sized,
+    {
+        Self::from_labelled_gpu_parts(fw, buf, size, None)
+    }
+
+    /// Constructs a new buffer from a [`wgpu::Buffer`], its byte `size` and a debug `label`.
+    ///
+    /// `label` should match the one `buf` was created with.
+    ///
+    /// # Safety
+    /// Same as [`BufOps::from_gpu_parts`].
+    fn from_labelled_gpu_parts(
+        fw: &Framework,
+        buf: wgpu::Buffer,
+        size: u64,
+        label: Option<String>,
+    ) -> Self;
 
     // --------- Decomposition fns -------------
 
@@ -73,11 +231,11 @@ where
 }
 
 /// Interface to get information, create and decompose GPU allocated images.
-pub trait ImgOps<'fw> {
+pub trait ImgOps {
     // --------- Information fns --------------
 
     /// Returns a [`wgpu::BindingResource`] of the image.
-    fn as_binding_resource(&self) -> wgpu::BindingResource;
+    fn as_binding_resource(&self) -> wgpu::BindingResource<'_>;
 
     /// Returns the [`wgpu::Texture`] that handles the GPU image.
     fn as_gpu_texture(&self) -> &wgpu::Texture;
@@ -88,20 +246,63 @@ pub trait ImgOps<'fw> {
     /// Returns the width and height of the image.
     fn dimensions(&self) -> (u32, u32);
 
+    /// Returns the debug label of the image, if any.
+    fn label(&self) -> Option<&str>;
+
     // ----------- Creation fns ---------------
 
     /// Constructs an empty image with the desired `width` and `height`.
-    fn new(fw: &'fw Framework, width: u32, height: u32) -> Self;
+    ///
+    /// The image has no debug label, which can be set by creating it with the `builder`
+    /// of its type instead.
+    ///
+    /// # Panics
+    /// If the pixel format cannot be used for this image type on the device (e.g. sRGB
+    /// formats as storage images), or if the image exceeds the limits of the device or
+    /// the memory budget. See [`ImgOps::try_new`].
+    fn new(fw: &Framework, width: u32, height: u32) -> Self;
+
+    /// Fallible version of [`ImgOps::new`], checking the pixel format against the device,
+    /// and the image dimensions against the `max_texture_dimension_2d` limit of the device
+    /// and the memory budget.
+    fn try_new(fw: &Framework, width: u32, height: u32) -> Result<Self, ImageInputError>
+    where
+        Self: Sized;
 
     /// Construct a new image from a bytes source `data` and its `width` and `height`.
     ///
-    /// If `data` doesn't fit the image perfectly, it panics.
-    fn from_bytes(fw: &'fw Framework, data: &[u8], width: u32, height: u32) -> Self;
+    /// # Panics
+    /// If `data` doesn't fit the image perfectly, if the pixel format cannot be used for
+    /// this image type on the device, or if the image exceeds the limits of the device
+    /// or the memory budget. See [`ImgOps::try_from_bytes`].
+    fn from_bytes(fw: &Framework, data: &[u8], width: u32, height: u32) -> Self;
+
+    /// Fallible version of [`ImgOps::from_bytes`], also checking that `data` fits the image.
+    fn try_from_bytes(
+        fw: &Framework,
+        data: &[u8],
+        width: u32,
+        height: u32,
+    ) -> Result<Self, ImageInputError>
+    where
+        Self: Sized;
 
-    fn from_gpu_parts(
-        fw: &'fw Framework,
+    /// Constructs an image from a [`wgpu::Texture`] and its [`wgpu::Extent3d`].
+    fn from_gpu_parts(fw: &Framework, texture: wgpu::Texture, dimensions: wgpu::Extent3d) -> Self
+    where
+        Self: Sized,
+    {
+        Self::from_labelled_gpu_parts(fw, texture, dimensions, None)
+    }
+
+    /// Constructs an image from a [`wgpu::Texture`], its [`wgpu::Extent3d`] and a debug `label`.
+    ///
+    /// `label` should match the one `texture` was created with.
+    fn from_labelled_gpu_parts(
+        fw: &Framework,
         texture: wgpu::Texture,
         dimensions: wgpu::Extent3d,
+        label: Option<String>,
     ) -> Self;
 
     // -------- Decomposition fns -------------
@@ -112,13 +313,44 @@ pub trait ImgOps<'fw> {
 
 /// Gives some information about the pixel format.
 pub trait PixelInfo {
+    /// Host representation of a single pixel of this format.
+    ///
+    /// 16 bit float formats use the raw `u16` bits of each channel.
+    type Pixel: bytemuck::Pod;
+
     fn byte_size() -> usize;
     fn wgpu_format() -> wgpu::TextureFormat;
     fn wgpu_texture_sample() -> wgpu::TextureSampleType;
+
+    /// Returns `true` if images of this format can be created with `usages` on `fw`,
+    /// taking into account the enabled features and the adapter format capabilities.
+    fn supports_usages(fw: &Framework, usages: wgpu::TextureUsages) -> bool {
+        let format = Self::wgpu_format();
+
+        fw.features().contains(format.required_features())
+            && fw
+                .texture_format_features(format)
+                .allowed_usages
+                .contains(usages)
+    }
+
+    /// Returns the sample type of images of this format bound as textures on `fw`.
+    ///
+    /// 32 bit float formats are only filterable with `wgpu::Features::FLOAT32_FILTERABLE`.
+    fn texture_sample(fw: &Framework) -> wgpu::TextureSampleType {
+        Self::wgpu_format()
+            .sample_type(None, Some(fw.features()))
+            .unwrap_or_else(Self::wgpu_texture_sample)
+    }
+
+    /// Returns `true` if images of this format can be bound as storage images on `fw`.
+    fn supports_storage(fw: &Framework) -> bool {
+        Self::supports_usages(fw, wgpu::TextureUsages::STORAGE_BINDING)
+    }
 }
 
 macro_rules! pixel_info_impl {
-    ($($name:ident, $size:expr, $format:expr, $sample:expr, #[$doc:meta]);+) => {
+    ($($name:ident, $pixel:ty, $format:expr, $sample:expr, #[$doc:meta]);+) => {
         use crate::primitives::PixelInfo;
 
         $(
@@ -126,8 +358,10 @@ macro_rules! pixel_info_impl {
             pub struct $name;
 
             impl PixelInfo for $name {
+                type Pixel = $pixel;
+
                 fn byte_size() -> usize {
-                    $size
+                    std::mem::size_of::<$pixel>()
                 }
 
                 fn wgpu_format() -> wgpu::TextureFormat {
@@ -144,12 +378,44 @@ macro_rules! pixel_info_impl {
 
 pub mod pixels {
     pixel_info_impl! {
-        Rgba8Uint, 4, wgpu::TextureFormat::Rgba8Uint, wgpu::TextureSampleType::Uint, #[doc = "Red, green, blue, and alpha channels. 8 bit integer per channel. Unsigned in shader."];
-        Rgba8UintNorm, 4, wgpu::TextureFormat::Rgba8Unorm, wgpu::TextureSampleType::Float { filterable: true }, #[doc = "Red, green, blue, and alpha channels. 8 bit integer per channel. [0, 255] converted to/from float [0, 1] in shader."];
-        Rgba8Sint, 4, wgpu::TextureFormat::Rgba8Sint, wgpu::TextureSampleType::Sint, #[doc = "Red, green, blue, and alpha channels. 8 bit integer per channel. Signed in shader."];
-        Rgba8SintNorm, 4, wgpu::TextureFormat::Rgba8Snorm, wgpu::TextureSampleType::Float { filterable: true }, #[doc = "Red, green, blue, and alpha channels. 8 bit integer per channel. [-127, 127] converted to/from float [-1, 1] in shader."];
-        Rgba32Float, 16, wgpu::TextureFormat::Rgba32Float, wgpu::TextureSampleType::Float { filterable: true }, #[doc = "Red, green, blue, and alpha channels. 32 bit float per channel. Float in shader."]
-        // Luma8, 1, wgpu::TextureFormat::R8Uint, wgpu::TextureSampleType::Uint, #[doc = "Grayscale 8 bit integer channel. Unsigned in shader."];
-        // Luma8Norm, 1, wgpu::TextureFormat::R8Unorm, wgpu::TextureSampleType::Float { filterable: false }, #[doc = "Grayscale 8 bit integer channel. Unsigned in shader. [0, 255] converted to/from float [0, 1] in shader."]
+        R8Uint, u8, wgpu::TextureFormat::R8Uint, wgpu::TextureSampleType::Uint, #[doc = "Red channel. 8 bit integer. Unsigned in shader."];
+        R8UintNorm, u8, wgpu::TextureFormat::R8Unorm, wgpu::TextureSampleType::Float { filterable: true }, #[doc = "Red channel. 8 bit integer. [0, 255] converted to/from float [0, 1] in shader."];
+        R8Sint, i8, wgpu::TextureFormat::R8Sint, wgpu::TextureSampleType::Sint, #[doc = "Red channel. 8 bit integer. Signed in shader."];
+        R8SintNorm, i8, wgpu::TextureFormat::R8Snorm, wgpu::TextureSampleType::Float { filterable: true }, #[doc = "Red channel. 8 bit integer. [-127, 127] converted to/from float [-1, 1] in shader."];
+        R16Uint, u16, wgpu::TextureFormat::R16Uint, wgpu::TextureSampleType::Uint, #[doc = "Red channel. 16 bit integer. Unsigned in shader."];
+        R16UintNorm, u16, wgpu::TextureFormat::R16Unorm, wgpu::TextureSampleType::Float { filterable: true }, #[doc = "Red channel. 16 bit integer. [0, 65535] converted to/from float [0, 1] in shader. Requires `wgpu::Features::TEXTURE_FORMAT_16BIT_NORM`."];
+        R16Sint, i16, wgpu::TextureFormat::R16Sint, wgpu::TextureSampleType::Sint, #[doc = "Red channel. 16 bit integer. Signed in shader."];
+        R16SintNorm, i16, wgpu::TextureFormat::R16Snorm, wgpu::TextureSampleType::Float { filterable: true }, #[doc = "Red channel. 16 bit integer. [-32767, 32767] converted to/from float [-1, 1] in shader. Requires `wgpu::Features::TEXTURE_FORMAT_16BIT_NORM`."];
+        R16Float, u16, wgpu::TextureFormat::R16Float, wgpu::TextureSampleType::Float { filterable: true }, #[doc = "Red channel. 16 bit float. Float in shader."];
+        R32Uint, u32, wgpu::TextureFormat::R32Uint, wgpu::TextureSampleType::Uint, #[doc = "Red channel. 32 bit integer. Unsigned in shader."];
+        R32Sint, i32, wgpu::TextureFormat::R32Sint, wgpu::TextureSampleType::Sint, #[doc = "Red channel. 32 bit integer. Signed in shader."];
+        R32Float, f32, wgpu::TextureFormat::R32Float, wgpu::TextureSampleType::Float { filterable: false }, #[doc = "Red channel. 32 bit float. Float in shader."];
+        Rg8Uint, [u8; 2], wgpu::TextureFormat::Rg8Uint, wgpu::TextureSampleType::Uint, #[doc = "Red and green channels. 8 bit integer per channel. Unsigned in shader."];
+        Rg8UintNorm, [u8; 2], wgpu::TextureFormat::Rg8Unorm, wgpu::TextureSampleType::Float { filterable: true }, #[doc = "Red and green channels. 8 bit integer per channel. [0, 255] converted to/from float [0, 1] in shader."];
+        Rg8Sint, [i8; 2], wgpu::TextureFormat::Rg8Sint, wgpu::TextureSampleType::Sint, #[doc = "Red and green channels. 8 bit integer per channel. Signed in shader."];
+        Rg8SintNorm, [i8; 2], wgpu::TextureFormat::Rg8Snorm, wgpu::TextureSampleType::Float { filterable: true }, #[doc = "Red and green channels. 8 bit integer per channel. [-127, 127] converted to/from float [-1, 1] in shader."];
+        Rg16Uint, [u16; 2], wgpu::TextureFormat::Rg16Uint, wgpu::TextureSampleType::Uint, #[doc = "Red and green channels. 16 bit integer per channel. Unsigned in shader."];
+        Rg16UintNorm, [u16; 2], wgpu::TextureFormat::Rg16Unorm, wgpu::TextureSampleType::Float { filterable: true }, #[doc = "Red and green channels. 16 bit integer per channel. [0, 65535] converted to/from float [0, 1] in shader. Requires `wgpu::Features::TEXTURE_FORMAT_16BIT_NORM`."];
+        Rg16Sint, [i16; 2], wgpu::TextureFormat::Rg16Sint, wgpu::TextureSampleType::Sint, #[doc = "Red and green channels. 16 bit integer per channel. Signed in shader."];
+        Rg16SintNorm, [i16; 2], wgpu::TextureFormat::Rg16Snorm, wgpu::TextureSampleType::Float { filterable: true }, #[doc = "Red and green channels. 16 bit integer per channel. [-32767, 32767] converted to/from float [-1, 1] in shader. Requires `wgpu::Features::TEXTURE_FORMAT_16BIT_NORM`."];
+        Rg16Float, [u16; 2], wgpu::TextureFormat::Rg16Float, wgpu::TextureSampleType::Float { filterable: true }, #[doc = "Red and green channels. 16 bit float per channel. Float in shader."];
+        Rg32Uint, [u32; 2], wgpu::TextureFormat::Rg32Uint, wgpu::TextureSampleType::Uint, #[doc = "Red and green channels. 32 bit integer per channel. Unsigned in shader."];
+        Rg32Sint, [i32; 2], wgpu::TextureFormat::Rg32Sint, wgpu::TextureSampleType::Sint, #[doc = "Red and green channels. 32 bit integer per channel. Signed in shader."];
+        Rg32Float, [f32; 2], wgpu::TextureFormat::Rg32Float, wgpu::TextureSampleType::Float { filterable: false }, #[doc = "Red and green channels. 32 bit float per channel. Float in shader."];
+        Rgba8Uint, [u8; 4], wgpu::TextureFormat::Rgba8Uint, wgpu::TextureSampleType::Uint, #[doc = "Red, green, blue, and alpha channels. 8 bit integer per channel. Unsigned in shader."];
+        Rgba8UintNorm, [u8; 4], wgpu::TextureFormat::Rgba8Unorm, wgpu::TextureSampleType::Float { filterable: true }, #[doc = "Red, green, blue, and alpha channels. 8 bit integer per channel. [0, 255] converted to/from float [0, 1] in shader."];
+        Rgba8Sint, [i8; 4], wgpu::TextureFormat::Rgba8Sint, wgpu::TextureSampleType::Sint, #[doc = "Red, green, blue, and alpha channels. 8 bit integer per channel. Signed in shader."];
+        Rgba8SintNorm, [i8; 4], wgpu::TextureFormat::Rgba8Snorm, wgpu::TextureSampleType::Float { filterable: true }, #[doc = "Red, green, blue, and alpha channels. 8 bit integer per channel. [-127, 127] converted to/from float [-1, 1] in shader."];
+        Rgba16Uint, [u16; 4], wgpu::TextureFormat::Rgba16Uint, wgpu::TextureSampleType::Uint, #[doc = "Red, green, blue, and alpha channels. 16 bit integer per channel. Unsigned in shader."];
+        Rgba16UintNorm, [u16; 4], wgpu::TextureFormat::Rgba16Unorm, wgpu::TextureSampleType::Float { filterable: true }, #[doc = "Red, green, blue, and alpha channels. 16 bit integer per channel. [0, 65535] converted to/from float [0, 1] in shader. Requires `wgpu::Features::TEXTURE_FORMAT_16BIT_NORM`."];
+        Rgba16Sint, [i16; 4], wgpu::TextureFormat::Rgba16Sint, wgpu::TextureSampleType::Sint, #[doc = "Red, green, blue, and alpha channels. 16 bit integer per channel. Signed in shader."];
+        Rgba16SintNorm, [i16; 4], wgpu::TextureFormat::Rgba16Snorm, wgpu::TextureSampleType::Float { filterable: true }, #[doc = "Red, green, blue, and alpha channels. 16 bit integer per channel. [-32767, 32767] converted to/from float [-1, 1] in shader. Requires `wgpu::Features::TEXTURE_FORMAT_16BIT_NORM`."];
+        Rgba16Float, [u16; 4], wgpu::TextureFormat::Rgba16Float, wgpu::TextureSampleType::Float { filterable: true }, #[doc = "Red, green, blue, and alpha channels. 16 bit float per channel. Float in shader."];
+        Rgba32Uint, [u32; 4], wgpu::TextureFormat::Rgba32Uint, wgpu::TextureSampleType::Uint, #[doc = "Red, green, blue, and alpha channels. 32 bit integer per channel. Unsigned in shader."];
+        Rgba32Sint, [i32; 4], wgpu::TextureFormat::Rgba32Sint, wgpu::TextureSampleType::Sint, #[doc = "Red, green, blue, and alpha channels. 32 bit integer per channel. Signed in shader."];
+        Rgba32Float, [f32; 4], wgpu::TextureFormat::Rgba32Float, wgpu::TextureSampleType::Float { filterable: false }, #[doc = "Red, green, blue, and alpha channels. 32 bit float per channel. Float in shader."];
+        Rgba8UintNormSrgb, [u8; 4], wgpu::TextureFormat::Rgba8UnormSrgb, wgpu::TextureSampleType::Float { filterable: true }, #[doc = "Red, green, blue, and alpha channels. 8 bit integer per channel. sRGB [0, 255] converted to/from linear float [0, 1] in shader."];
+        Bgra8UintNorm, [u8; 4], wgpu::TextureFormat::Bgra8Unorm, wgpu::TextureSampleType::Float { filterable: true }, #[doc = "Blue, green, red, and alpha channels. 8 bit integer per channel. [0, 255] converted to/from float [0, 1] in shader."];
+        Bgra8UintNormSrgb, [u8; 4], wgpu::TextureFormat::Bgra8UnormSrgb, wgpu::TextureSampleType::Float { filterable: true }, #[doc = "Blue, green, red, and alpha channels. 8 bit integer per channel. sRGB [0, 255] converted to/from linear float [0, 1] in shader."]
     }
 }
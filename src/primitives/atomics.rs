@@ -0,0 +1,160 @@
+use crate::{Framework, GpuAppendBuffer, GpuAtomicCounter, GpuBuffer, GpuStructBuffer};
+
+use super::{buffers::BufferResult, BufOps};
+
+impl GpuAtomicCounter {
+    /// Constructs a new counter initialised to `value`.
+    pub fn new(fw: &Framework, value: u32) -> Self {
+        let buf = GpuBuffer::builder(fw)
+            .label("GpuAtomicCounter::new")
+            .data(&[value])
+            .build()
+            .unwrap_or_else(|err| panic!("{}", err));
+
+        Self { buf }
+    }
+
+    /// Sets the counter to 0. The operation is instantly offloaded.
+    pub fn reset(&self) {
+        self.set(0);
+    }
+
+    /// Sets the counter to `value`. The operation is instantly offloaded.
+    pub fn set(&self, value: u32) {
+        self.buf
+            .write(&[value])
+            .expect("Cannot fail writing a single element.");
+    }
+
+    /// Pulls the current value of the counter.
+    pub async fn read(&self) -> BufferResult<u32> {
+        let mut value = [0];
+        self.buf.read(&mut value).await?;
+
+        Ok(value[0])
+    }
+
+    /// Blocking version of `GpuAtomicCounter::read()`.
+    pub fn read_blocking(&self) -> BufferResult<u32> {
+        self.buf.fw.block_on(self.read())
+    }
+
+    /// Returns a [`wgpu::BindingResource`] of the counter.
+    pub fn as_binding_resource(&self) -> wgpu::BindingResource<'_> {
+        self.buf.as_binding_resource()
+    }
+
+    /// Returns the [`wgpu::Buffer`] that handles the GPU data of the counter.
+    pub fn as_gpu_buffer(&self) -> &wgpu::Buffer {
+        self.buf.as_gpu_buffer()
+    }
+}
+
+impl<T> GpuAppendBuffer<T>
+where
+    T: bytemuck::Pod,
+{
+    /// Constructs a new empty append buffer able to hold `capacity` elements.
+    pub fn with_capacity(fw: &Framework, capacity: u64) -> Self {
+        let buf = GpuStructBuffer::builder(fw)
+            .label("GpuAppendBuffer::with_capacity")
+            .capacity(capacity)
+            .build()
+            .unwrap_or_else(|err| panic!("{}", err));
+
+        Self { buf }
+    }
+
+    /// Returns the number of elements the buffer can hold.
+    pub fn capacity(&self) -> u64 {
+        self.buf.capacity()
+    }
+
+    /// Empties the buffer by setting its count to 0. The operation is instantly offloaded.
+    pub fn reset(&self) {
+        self.buf.write_header(&0);
+    }
+
+    /// Pulls the number of appended elements.
+    ///
+    /// It may exceed [`GpuAppendBuffer::capacity`] if the shader attempted
+    /// to append more elements than the buffer can hold.
+    pub async fn count(&self) -> BufferResult<u32> {
+        self.buf.read_header().await
+    }
+
+    /// Pulls only the appended elements into a [`Vec`].
+    pub async fn read_used(&self) -> BufferResult<Vec<T>> {
+        let used = (self.count().await? as u64).min(self.capacity());
+
+        let mut buf = vec![T::zeroed(); used as usize];
+        self.buf.read_elements(&mut buf).await?;
+
+        Ok(buf)
+    }
+
+    /// Blocking version of `GpuAppendBuffer::count()`.
+    pub fn count_blocking(&self) -> BufferResult<u32> {
+        self.buf.fw.block_on(self.count())
+    }
+
+    /// Blocking version of `GpuAppendBuffer::read_used()`.
+    pub fn read_used_blocking(&self) -> BufferResult<Vec<T>> {
+        self.buf.fw.block_on(self.read_used())
+    }
+
+    /// Returns the underlying [`GpuStructBuffer`].
+    pub fn as_struct_buffer(&self) -> &GpuStructBuffer<u32, T> {
+        &self.buf
+    }
+}
+
+/// Returns a WGSL snippet declaring a [`GpuAppendBuffer`] named `name` of `element_type`
+/// elements at `group` and `binding`, alongside a `<name>_append(value) -> bool` function.
+///
+/// The function returns `false` when the buffer is full and the value is discarded.
+///
+/// ### Example output for `wgsl_append_buffer("hits", "vec2<u32>", 0, 1)`:
+/// ```ignore
+/// struct hits_AppendBuffer {
+///     count: atomic<u32>,
+///     data: array<vec2<u32>>,
+/// }
+///
+/// @group(0) @binding(1)
+/// var<storage, read_write> hits: hits_AppendBuffer;
+///
+/// fn hits_append(value: vec2<u32>) -> bool {
+///     let index = atomicAdd(&hits.count, 1u);
+///     if (index >= arrayLength(&hits.data)) {
+///         return false;
+///     }
+///     hits.data[index] = value;
+///     return true;
+/// }
+/// ```
+pub fn wgsl_append_buffer(name: &str, element_type: &str, group: u32, binding: u32) -> String {
+    format!(
+        "struct {name}_AppendBuffer {{
+    count: atomic<u32>,
+    data: array<{ty}>,
+}}
+
+@group({group}) @binding({binding})
+var<storage, read_write> {name}: {name}_AppendBuffer;
+
+fn {name}_append(value: {ty}) -> bool {{
+    let index = atomicAdd(&{name}.count, 1u);
+    if (index >= arrayLength(&{name}.data)) {{
+        return false;
+    }}
+    {name}.data[index] = value;
+    return true;
+}}
+",
+        name = name,
+        ty = element_type,
+        group = group,
+        binding = binding,
+    )
+}
@@ -1,22 +1,58 @@
-use std::marker::PhantomData;
+use std::{marker::PhantomData, ops::Range};
 
 use thiserror::Error;
-use wgpu::util::DeviceExt;
 
-use crate::{GpuBuffer, GpuUniformBuffer};
+use crate::{
+    kernel::ShaderError,
+    memory::{Allocation, MemoryKind, OutOfBudget},
+    Framework, GpuBuffer, GpuUniformBuffer,
+};
 
 use super::BufOps;
 
-// TODO https://github.com/bitflags/bitflags/issues/180
-// TODO Unsure wether MAP_READ and MAP_WRITE should only be present on certain buffers
-const GPU_BUFFER_USAGES: wgpu::BufferUsages = wgpu::BufferUsages::from_bits_truncate(
-    wgpu::BufferUsages::STORAGE.bits()
-        | wgpu::BufferUsages::COPY_SRC.bits()
-        | wgpu::BufferUsages::COPY_DST.bits(),
-);
-const GPU_UNIFORM_USAGES: wgpu::BufferUsages = wgpu::BufferUsages::from_bits_truncate(
-    wgpu::BufferUsages::UNIFORM.bits() | wgpu::BufferUsages::COPY_DST.bits(),
-);
+const GPU_BUFFER_USAGES: wgpu::BufferUsages = BufferRole::Storage.usages();
+const GPU_UNIFORM_USAGES: wgpu::BufferUsages = BufferRole::Uniform.usages();
+
+/// Intended use of a buffer. Higher-level alternative to raw [`wgpu::BufferUsages`].
+///
+/// Every role is `COPY_SRC` and `COPY_DST` usable, so the buffer can always be
+/// read back and written from the CPU.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BufferRole {
+    /// Storage buffer. Default role of [`GpuBuffer`].
+    Storage,
+    /// Uniform buffer. Default role of [`GpuUniformBuffer`].
+    Uniform,
+    /// Storage buffer that can also be used as indirect dispatch or draw arguments.
+    Indirect,
+    /// Storage buffer that can also be used as a vertex buffer by `wgpu` render pipelines.
+    Vertex,
+    /// Storage buffer that can also be used as an index buffer by `wgpu` render pipelines.
+    Index,
+}
+
+impl BufferRole {
+    /// Returns the [`wgpu::BufferUsages`] of the role.
+    pub const fn usages(self) -> wgpu::BufferUsages {
+        let role = match self {
+            BufferRole::Storage => wgpu::BufferUsages::STORAGE,
+            BufferRole::Uniform => wgpu::BufferUsages::UNIFORM,
+            BufferRole::Indirect => wgpu::BufferUsages::from_bits_truncate(
+                wgpu::BufferUsages::STORAGE.bits() | wgpu::BufferUsages::INDIRECT.bits(),
+            ),
+            BufferRole::Vertex => wgpu::BufferUsages::from_bits_truncate(
+                wgpu::BufferUsages::STORAGE.bits() | wgpu::BufferUsages::VERTEX.bits(),
+            ),
+            BufferRole::Index => wgpu::BufferUsages::from_bits_truncate(
+                wgpu::BufferUsages::STORAGE.bits() | wgpu::BufferUsages::INDEX.bits(),
+            ),
+        };
+
+        wgpu::BufferUsages::from_bits_truncate(
+            role.bits() | wgpu::BufferUsages::COPY_SRC.bits() | wgpu::BufferUsages::COPY_DST.bits(),
+        )
+    }
+}
 
 pub type BufferResult<T> = Result<T, BufferError>;
 
@@ -24,116 +60,360 @@ pub type BufferResult<T> = Result<T, BufferError>;
 pub enum BufferError {
     #[error(transparent)]
     AsyncMapError(#[from] wgpu::BufferAsyncError),
+    #[error(
+        "Buffer {} cannot be read (missing `wgpu::BufferUsages::COPY_SRC` usage).",
+        .label.as_deref().unwrap_or("<unlabelled>")
+    )]
+    NotReadable { label: Option<String> },
+    #[error("Invalid buffer usages {0:?}.")]
+    InvalidUsage(wgpu::BufferUsages),
+    #[error(
+        "Initial data does not fit the buffer (capacity {capacity} elements, data {len} elements)."
+    )]
+    DataTooLarge { capacity: u64, len: u64 },
+    #[error(
+        "Buffer {} of {size} bytes exceeds the `{limit}` limit of the device ({max} bytes).",
+        .label.as_deref().unwrap_or("<unlabelled>")
+    )]
+    ExceedsLimit {
+        label: Option<String>,
+        limit: &'static str,
+        size: u64,
+        max: u64,
+    },
+    #[error(
+        "Download of buffer {} was canceled before completing (device lost or destroyed).",
+        .label.as_deref().unwrap_or("<unlabelled>")
+    )]
+    DownloadCanceled { label: Option<String> },
+    #[error("Array alignment {0} is not a power of two of at least 4 bytes.")]
+    InvalidAlignment(u64),
+    #[error(
+        "Write of {size} bytes at offset {offset} into buffer {} is not a multiple of `wgpu::COPY_BUFFER_ALIGNMENT`.",
+        .label.as_deref().unwrap_or("<unlabelled>")
+    )]
+    UnalignedWrite {
+        label: Option<String>,
+        offset: u64,
+        size: u64,
+    },
+    #[error("No reflected binding at group {group}, binding {binding}.")]
+    BindingNotFound { group: u32, binding: u32 },
+    #[error(
+        "Buffer {} does not match the layout at group {group}, binding {binding} ({what}: shader {shader} bytes, host {host} bytes).",
+        .label.as_deref().unwrap_or("<unlabelled>")
+    )]
+    LayoutMismatch {
+        label: Option<String>,
+        group: u32,
+        binding: u32,
+        what: &'static str,
+        shader: u64,
+        host: u64,
+    },
+    #[error(transparent)]
+    ShaderError(#[from] ShaderError),
+    #[error(transparent)]
+    OutOfBudget(#[from] OutOfBudget),
+}
+
+/// Checks that a buffer of `size` bytes fits the limits of `fw`.
+pub(crate) fn check_buffer_size(
+    fw: &Framework,
+    size: u64,
+    label: Option<&str>,
+) -> BufferResult<()> {
+    let max = fw.limits().max_buffer_size;
+
+    if size > max {
+        return Err(BufferError::ExceedsLimit {
+            label: label.map(str::to_owned),
+            limit: "max_buffer_size",
+            size,
+            max,
+        });
+    }
+
+    Ok(())
+}
+
+/// Downloads the `range` bytes of a `COPY_SRC` usable buffer from the GPU.
+///
+/// The returned future polls the device when polled, and is woken once the download completes.
+pub(crate) async fn download_buffer(
+    fw: &Framework,
+    buffer: &wgpu::Buffer,
+    range: Range<u64>,
+    label: Option<&str>,
+) -> BufferResult<wgpu::util::DownloadBuffer> {
+    // Accounts for the staging buffer allocated by `wgpu::util::DownloadBuffer`.
+    let _staging = fw.allocate(MemoryKind::Staging, range.end - range.start, label)?;
+
+    let (tx, mut rx) = futures::channel::oneshot::channel();
+    let slice = buffer.slice(range);
+    wgpu::util::DownloadBuffer::read_buffer(&fw.device, &fw.queue, &slice, move |result| {
+        // The receiver is only gone when the download future was dropped: nobody awaits the result.
+        let _ = tx.send(result);
+    });
+
+    let result = futures::future::poll_fn(|cx| fw.poll_receiver(&mut rx, cx))
+        .await
+        .map_err(|_| BufferError::DownloadCanceled {
+            label: label.map(str::to_owned),
+        })?;
+
+    Ok(result?)
+}
+
+/// Builder of GPU buffers with custom label, usages and initial data.
+///
+/// Created by [`GpuBuffer::builder`] or [`GpuUniformBuffer::builder`].
+///
+/// # Example
+/// ```no_run
+/// # use gpgpu::{primitives::buffers::BufferRole, *};
+/// # let fw = Framework::default();
+/// let args = GpuBuffer::<u32>::builder(&fw)
+///     .label("Indirect arguments")
+///     .role(BufferRole::Indirect)
+///     .data(&[1, 1, 1])
+///     .build()
+///     .unwrap();
+/// ```
+pub struct BufferBuilder<'a, T, B> {
+    fw: Framework,
+    label: Option<String>,
+    usage: wgpu::BufferUsages,
+    mapped_at_creation: bool,
+    capacity: Option<u64>,
+    data: Option<&'a [T]>,
+    marker: PhantomData<B>,
 }
 
-impl<'fw, T> BufOps<'fw, T> for GpuBuffer<'fw, T>
+impl<'a, T, B> BufferBuilder<'a, T, B>
 where
     T: bytemuck::Pod,
+    B: BufOps<T> + sealed::BuildableBuffer,
 {
-    fn size(&self) -> u64 {
-        self.size
+    fn new(fw: &Framework, usage: wgpu::BufferUsages) -> Self {
+        Self {
+            fw: fw.clone(),
+            label: None,
+            usage,
+            mapped_at_creation: false,
+            capacity: None,
+            data: None,
+            marker: PhantomData,
+        }
     }
 
-    fn as_gpu_buffer(&self) -> &wgpu::Buffer {
-        &self.buf
+    /// Sets the debug label of the buffer.
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
     }
 
-    fn with_capacity(fw: &'fw crate::Framework, capacity: u64) -> Self {
-        let size = capacity * std::mem::size_of::<T>() as u64;
-        let buf = fw.device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("GpuBuffer::with_capacity"),
-            size,
-            usage: GPU_BUFFER_USAGES,
-            mapped_at_creation: false,
-        });
+    /// Sets the raw [`wgpu::BufferUsages`] of the buffer, replacing the default ones.
+    ///
+    /// Reading the buffer back with [`BufOps::read`] requires `wgpu::BufferUsages::COPY_SRC`.
+    pub fn usage(mut self, usage: wgpu::BufferUsages) -> Self {
+        self.usage = usage;
+        self
+    }
 
-        Self {
-            fw,
-            buf,
-            size,
-            marker: PhantomData,
-        }
+    /// Sets the usages of the buffer from a [`BufferRole`], replacing the default ones.
+    pub fn role(mut self, role: BufferRole) -> Self {
+        self.usage = role.usages();
+        self
     }
 
-    fn from_slice(fw: &'fw crate::Framework, slice: &[T]) -> Self {
-        let size = std::mem::size_of_val(slice) as u64;
-        let buf = fw
-            .device
-            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("GpuBuffer::from_slice"),
-                contents: bytemuck::cast_slice(slice),
-                usage: GPU_BUFFER_USAGES,
+    /// Leaves the buffer mapped after its creation.
+    ///
+    /// The mapped range can be accessed through [`BufOps::as_gpu_buffer`] and
+    /// must be unmapped with [`wgpu::Buffer::unmap`] before any GPU usage.
+    pub fn mapped_at_creation(mut self, mapped: bool) -> Self {
+        self.mapped_at_creation = mapped;
+        self
+    }
+
+    /// Sets the number of elements the buffer can hold.
+    ///
+    /// Defaults to the length of the initial data, if any, or to 0.
+    pub fn capacity(mut self, capacity: u64) -> Self {
+        self.capacity = Some(capacity);
+        self
+    }
+
+    /// Sets the initial data of the buffer.
+    pub fn data(mut self, data: &'a [T]) -> Self {
+        self.data = Some(data);
+        self
+    }
+
+    /// Creates the buffer.
+    pub fn build(self) -> BufferResult<B> {
+        let data_len = self.data.map_or(0, |data| data.len() as u64);
+        let capacity = self.capacity.unwrap_or(data_len);
+
+        if data_len > capacity {
+            return Err(BufferError::DataTooLarge {
+                capacity,
+                len: data_len,
             });
+        }
 
-        Self {
-            fw,
-            buf,
-            size,
-            marker: PhantomData,
+        // Without `wgpu::Features::MAPPABLE_PRIMARY_BUFFERS`, mappable buffers
+        // can only be combined with their copy usage.
+        let mappable_primary = self
+            .fw
+            .features()
+            .contains(wgpu::Features::MAPPABLE_PRIMARY_BUFFERS);
+        let invalid_map_read = self.usage.contains(wgpu::BufferUsages::MAP_READ)
+            && !(wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST).contains(self.usage);
+        let invalid_map_write = self.usage.contains(wgpu::BufferUsages::MAP_WRITE)
+            && !(wgpu::BufferUsages::MAP_WRITE | wgpu::BufferUsages::COPY_SRC).contains(self.usage);
+
+        if self.usage.is_empty() || (!mappable_primary && (invalid_map_read || invalid_map_write)) {
+            return Err(BufferError::InvalidUsage(self.usage));
         }
+
+        let size = capacity.saturating_mul(std::mem::size_of::<T>() as u64);
+        check_buffer_size(&self.fw, size, self.label.as_deref())?;
+
+        // Empty data leaves nothing to upload, and mapping empty buffers fails on some backends.
+        let data = self.data.filter(|data| !data.is_empty());
+        let mapped_at_creation = self.mapped_at_creation || data.is_some();
+
+        // Mapped buffers must have a size multiple of `wgpu::COPY_BUFFER_ALIGNMENT`.
+        let alloc_size = if mapped_at_creation {
+            wgpu::util::align_to(size, wgpu::COPY_BUFFER_ALIGNMENT)
+        } else {
+            size
+        };
+
+        let allocation = self
+            .fw
+            .allocate(MemoryKind::Buffer, alloc_size, self.label.as_deref())?;
+
+        let buf = self.fw.device.create_buffer(&wgpu::BufferDescriptor {
+            label: self.label.as_deref(),
+            size: alloc_size,
+            usage: self.usage,
+            mapped_at_creation,
+        });
+
+        if let Some(data) = data {
+            let bytes: &[u8] = bytemuck::cast_slice(data);
+            buf.slice(..)
+                .get_mapped_range_mut()
+                .get_mut(..bytes.len())
+                .expect("Mapped range smaller than the buffer data.")
+                .copy_from_slice(bytes);
+
+            if !self.mapped_at_creation {
+                buf.unmap();
+            }
+        }
+
+        Ok(B::from_buffer(self.fw, buf, size, self.label, allocation))
     }
+}
 
-    fn from_gpu_parts(fw: &'fw crate::Framework, buf: wgpu::Buffer, size: u64) -> Self {
+pub(crate) mod sealed {
+    /// Buffer types that can be created by a [`BufferBuilder`](super::BufferBuilder).
+    pub trait BuildableBuffer {
+        /// Wraps `buf`, keeping the `allocation` reserved for it alive.
+        fn from_buffer(
+            fw: crate::Framework,
+            buf: wgpu::Buffer,
+            size: u64,
+            label: Option<String>,
+            allocation: crate::memory::Allocation,
+        ) -> Self;
+    }
+}
+
+impl<T> sealed::BuildableBuffer for GpuBuffer<T>
+where
+    T: bytemuck::Pod,
+{
+    fn from_buffer(
+        fw: Framework,
+        buf: wgpu::Buffer,
+        size: u64,
+        label: Option<String>,
+        allocation: Allocation,
+    ) -> Self {
         Self {
             fw,
             buf,
             size,
+            label,
+            _allocation: allocation,
             marker: PhantomData,
         }
     }
-
-    fn into_gpu_parts(self) -> (wgpu::Buffer, u64) {
-        (self.buf, self.size)
-    }
 }
 
-impl<'fw, T> GpuBuffer<'fw, T>
+impl<T> BufOps<T> for GpuBuffer<T>
 where
     T: bytemuck::Pod,
 {
-    /// Pulls some elements from the [`GpuBuffer`] into `buf`, returning how many elements were read.
-    pub async fn read(&self, buf: &mut [T]) -> BufferResult<u64> {
-        let output_size = std::mem::size_of_val(buf) as u64;
-        let download_size = if output_size > self.size {
-            self.size
-        } else {
-            output_size
-        };
+    fn size(&self) -> u64 {
+        self.size
+    }
+
+    fn as_gpu_buffer(&self) -> &wgpu::Buffer {
+        &self.buf
+    }
+
+    fn framework(&self) -> &crate::Framework {
+        &self.fw
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
 
-        let (tx, rx) = futures::channel::oneshot::channel();
-        wgpu::util::DownloadBuffer::read_buffer(
-            &self.fw.device,
-            &self.fw.queue,
-            &self.buf.slice(..download_size),
-            move |result| {
-                tx.send(result)
-                    .unwrap_or_else(|_| panic!("Failed to download buffer."));
-            },
-        );
-        let download = rx.await.unwrap().unwrap();
+    fn with_capacity(fw: &Framework, capacity: u64) -> Self {
+        Self::try_with_capacity(fw, capacity).unwrap_or_else(|err| panic!("{}", err))
+    }
 
-        buf.copy_from_slice(bytemuck::cast_slice(&download));
+    fn try_with_capacity(fw: &Framework, capacity: u64) -> BufferResult<Self> {
+        Self::builder(fw).capacity(capacity).build()
+    }
+
+    fn from_slice(fw: &Framework, slice: &[T]) -> Self {
+        Self::try_from_slice(fw, slice).unwrap_or_else(|err| panic!("{}", err))
+    }
 
-        Ok(download_size)
+    fn try_from_slice(fw: &Framework, slice: &[T]) -> BufferResult<Self> {
+        Self::builder(fw).data(slice).build()
     }
 
-    /// Pulls all the elements from the [`GpuBuffer`] into a [`Vec`].
-    pub async fn read_vec(&self) -> BufferResult<Vec<T>> {
-        // Safety: Since T is Pod: Zeroed + ... it is safe to use zeroed() to init it.
-        let mut buf = vec![unsafe { std::mem::zeroed() }; self.capacity() as usize];
-        self.read(&mut buf).await?;
+    fn from_labelled_gpu_parts(
+        fw: &Framework,
+        buf: wgpu::Buffer,
+        size: u64,
+        label: Option<String>,
+    ) -> Self {
+        let allocation = fw.track(MemoryKind::Buffer, buf.size(), label.as_deref());
 
-        Ok(buf)
+        <Self as sealed::BuildableBuffer>::from_buffer(fw.clone(), buf, size, label, allocation)
     }
 
-    /// Blocking version of `GpuBuffer::read()`.
-    pub fn read_blocking(&self, buf: &mut [T]) -> BufferResult<u64> {
-        futures::executor::block_on(self.read(buf))
+    fn into_gpu_parts(self) -> (wgpu::Buffer, u64) {
+        (self.buf, self.size)
     }
+}
 
-    /// Blocking version of `GpuBuffer::read_vec()`.
-    pub fn read_vec_blocking(&self) -> BufferResult<Vec<T>> {
-        futures::executor::block_on(self.read_vec())
+impl<T> GpuBuffer<T>
+where
+    T: bytemuck::Pod,
+{
+    /// Creates a [`BufferBuilder`] of a [`GpuBuffer`]. Its default role is [`BufferRole::Storage`].
+    pub fn builder<'a>(fw: &Framework) -> BufferBuilder<'a, T, Self> {
+        BufferBuilder::new(fw, GPU_BUFFER_USAGES)
     }
 
     /// Writes a buffer into this [`GpuBuffer`], returning how many elements were written. The operation is instantly offloaded.
@@ -156,7 +436,7 @@ where
             .fw
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                label: Some("GpuBuffer::write"),
+                label: Some(self.label.as_deref().unwrap_or("GpuBuffer::write")),
             });
         self.fw.queue.submit(Some(encoder.finish()));
 
@@ -164,7 +444,29 @@ where
     }
 }
 
-impl<'fw, T> BufOps<'fw, T> for GpuUniformBuffer<'fw, T>
+impl<T> sealed::BuildableBuffer for GpuUniformBuffer<T>
+where
+    T: bytemuck::Pod,
+{
+    fn from_buffer(
+        fw: Framework,
+        buf: wgpu::Buffer,
+        size: u64,
+        label: Option<String>,
+        allocation: Allocation,
+    ) -> Self {
+        Self {
+            fw,
+            buf,
+            size,
+            label,
+            _allocation: allocation,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> BufOps<T> for GpuUniformBuffer<T>
 where
     T: bytemuck::Pod,
 {
@@ -176,49 +478,39 @@ where
         &self.buf
     }
 
-    fn with_capacity(fw: &'fw crate::Framework, capacity: u64) -> Self {
-        let size = capacity * std::mem::size_of::<T>() as u64;
+    fn framework(&self) -> &crate::Framework {
+        &self.fw
+    }
 
-        let buf = fw.device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("GpuUniformBuffer::with_capacity"),
-            size,
-            usage: GPU_UNIFORM_USAGES,
-            mapped_at_creation: false,
-        });
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
 
-        Self {
-            fw,
-            buf,
-            size,
-            marker: PhantomData,
-        }
+    fn with_capacity(fw: &Framework, capacity: u64) -> Self {
+        Self::try_with_capacity(fw, capacity).unwrap_or_else(|err| panic!("{}", err))
     }
 
-    fn from_slice(fw: &'fw crate::Framework, slice: &[T]) -> Self {
-        let size = std::mem::size_of_val(slice) as u64;
-        let buf = fw
-            .device
-            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("GpuUniformBuffer::from_slice"),
-                contents: bytemuck::cast_slice(slice),
-                usage: GPU_UNIFORM_USAGES,
-            });
+    fn try_with_capacity(fw: &Framework, capacity: u64) -> BufferResult<Self> {
+        Self::builder(fw).capacity(capacity).build()
+    }
 
-        Self {
-            fw,
-            buf,
-            size,
-            marker: PhantomData,
-        }
+    fn from_slice(fw: &Framework, slice: &[T]) -> Self {
+        Self::try_from_slice(fw, slice).unwrap_or_else(|err| panic!("{}", err))
     }
 
-    fn from_gpu_parts(fw: &'fw crate::Framework, buf: wgpu::Buffer, size: u64) -> Self {
-        Self {
-            fw,
-            buf,
-            size,
-            marker: PhantomData,
-        }
+    fn try_from_slice(fw: &Framework, slice: &[T]) -> BufferResult<Self> {
+        Self::builder(fw).data(slice).build()
+    }
+
+    fn from_labelled_gpu_parts(
+        fw: &Framework,
+        buf: wgpu::Buffer,
+        size: u64,
+        label: Option<String>,
+    ) -> Self {
+        let allocation = fw.track(MemoryKind::Buffer, buf.size(), label.as_deref());
+
+        <Self as sealed::BuildableBuffer>::from_buffer(fw.clone(), buf, size, label, allocation)
     }
 
     fn into_gpu_parts(self) -> (wgpu::Buffer, u64) {
@@ -226,10 +518,15 @@ where
     }
 }
 
-impl<'fw, T> GpuUniformBuffer<'fw, T>
+impl<T> GpuUniformBuffer<T>
 where
     T: bytemuck::Pod,
 {
+    /// Creates a [`BufferBuilder`] of a [`GpuUniformBuffer`]. Its default role is [`BufferRole::Uniform`].
+    pub fn builder<'a>(fw: &Framework) -> BufferBuilder<'a, T, Self> {
+        BufferBuilder::new(fw, GPU_UNIFORM_USAGES)
+    }
+
     /// Writes a buffer into this [`GpuUniformBuffer`], returning how many elements were written. The operation is instantly offloaded.
     ///
     /// This function will attempt to write the entire contents of `buf` unless its capacity
@@ -250,7 +547,7 @@ where
             .fw
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                label: Some("GpuUniformBuffer::write"),
+                label: Some(self.label.as_deref().unwrap_or("GpuUniformBuffer::write")),
             });
         self.fw.queue.submit(Some(encoder.finish()));
 
@@ -1,246 +1,1692 @@
-use std::marker::PhantomData;
-
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    marker::PhantomData,
+    sync::{Arc, Mutex, OnceLock, PoisonError},
+};
+
+use bytemuck::Zeroable;
 use thiserror::Error;
 use wgpu::util::DeviceExt;
 
-use crate::{GpuConstImage, GpuImage};
+use crate::{
+    kernel::ShaderError,
+    memory::{self, MemoryKind, OutOfBudget},
+    Framework, GpuBuffer, GpuConstImage, GpuImage,
+};
 
-use super::{ImgOps, PixelInfo};
+use super::{BufOps, ImgOps, PixelInfo};
 
 // TODO https://github.com/bitflags/bitflags/issues/180
-const GPU_IMAGE_USAGES: wgpu::TextureUsages = wgpu::TextureUsages::from_bits_truncate(
+pub(crate) const GPU_IMAGE_USAGES: wgpu::TextureUsages = wgpu::TextureUsages::from_bits_truncate(
     wgpu::TextureUsages::STORAGE_BINDING.bits()
+        | wgpu::TextureUsages::TEXTURE_BINDING.bits()
         | wgpu::TextureUsages::COPY_SRC.bits()
         | wgpu::TextureUsages::COPY_DST.bits(),
 );
-const GPU_CONST_IMAGE_USAGES: wgpu::TextureUsages = wgpu::TextureUsages::from_bits_truncate(
-    wgpu::TextureUsages::TEXTURE_BINDING.bits() | wgpu::TextureUsages::COPY_DST.bits(),
-);
+pub(crate) const GPU_CONST_IMAGE_USAGES: wgpu::TextureUsages =
+    wgpu::TextureUsages::from_bits_truncate(
+        wgpu::TextureUsages::TEXTURE_BINDING.bits()
+            | wgpu::TextureUsages::COPY_SRC.bits()
+            | wgpu::TextureUsages::COPY_DST.bits(),
+    );
 
 #[derive(Error, Debug)]
 pub enum ImageOutputError {
     #[error(transparent)]
     BufferError(#[from] crate::primitives::buffers::BufferError),
     #[error(
-        "Output is too small (required size {required} bytes, current size {current} bytes). "
+        "Output of image {} is too small (required size {required} bytes, current size {current} bytes). ",
+        .label.as_deref().unwrap_or("<unlabelled>")
+    )]
+    BufferTooSmall {
+        label: Option<String>,
+        required: usize,
+        current: usize,
+    },
+    #[error(
+        "Region ({x}, {y}) {width}x{height} is out of bounds of image {}.",
+        .label.as_deref().unwrap_or("<unlabelled>")
+    )]
+    RegionOutOfBounds {
+        label: Option<String>,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+    #[error(
+        "Layer {layer} is out of bounds of image {} ({layers} layers).",
+        .label.as_deref().unwrap_or("<unlabelled>")
     )]
-    BufferTooSmall { required: usize, current: usize },
+    LayerOutOfBounds {
+        label: Option<String>,
+        layer: u32,
+        layers: u32,
+    },
+    #[error(
+        "Mip level {level} is out of bounds of image {} ({levels} levels).",
+        .label.as_deref().unwrap_or("<unlabelled>")
+    )]
+    MipLevelOutOfBounds {
+        label: Option<String>,
+        level: u32,
+        levels: u32,
+    },
+    #[error(transparent)]
+    OutOfBudget(#[from] OutOfBudget),
 }
 
+/// Error returned when an image cannot be created or written from host data.
+///
+/// The input data may not split into whole pixels or rows, or not fit the image, and the
+/// written region, layer or mip level may be out of bounds. Images can also fail to be
+/// created when their pixel format is unsupported or the memory budget is exceeded.
+/// Every variant carries the `label` of the image, if any.
 #[derive(Error, Debug)]
 pub enum ImageInputError {
-    #[error("Input does not contains an integer number of pixels.")]
-    NotIntegerPixelNumber,
-    #[error("Input does not contains an integer number of rows.")]
-    NotIntegerRowNumber,
+    #[error(
+        "Input of image {} does not contains an integer number of pixels.",
+        .label.as_deref().unwrap_or("<unlabelled>")
+    )]
+    NotIntegerPixelNumber { label: Option<String> },
+    #[error(
+        "Input of image {} does not contains an integer number of rows.",
+        .label.as_deref().unwrap_or("<unlabelled>")
+    )]
+    NotIntegerRowNumber { label: Option<String> },
+    #[error(
+        "Input of image {} does not fit the image (required size {required} bytes, current size {current} bytes).",
+        .label.as_deref().unwrap_or("<unlabelled>")
+    )]
+    SizeMismatch {
+        label: Option<String>,
+        required: usize,
+        current: usize,
+    },
+    #[error(
+        "Image {} format {format:?} does not support usages {usage:?} on this device.",
+        .label.as_deref().unwrap_or("<unlabelled>")
+    )]
+    UnsupportedFormat {
+        label: Option<String>,
+        format: wgpu::TextureFormat,
+        usage: wgpu::TextureUsages,
+    },
+    #[error(
+        "Region ({x}, {y}) {width}x{height} is out of bounds of image {}.",
+        .label.as_deref().unwrap_or("<unlabelled>")
+    )]
+    RegionOutOfBounds {
+        label: Option<String>,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+    #[error(
+        "Layer {layer} is out of bounds of image {} ({layers} layers).",
+        .label.as_deref().unwrap_or("<unlabelled>")
+    )]
+    LayerOutOfBounds {
+        label: Option<String>,
+        layer: u32,
+        layers: u32,
+    },
+    #[error(
+        "Mip level {level} is out of bounds of image {} ({levels} levels).",
+        .label.as_deref().unwrap_or("<unlabelled>")
+    )]
+    MipLevelOutOfBounds {
+        label: Option<String>,
+        level: u32,
+        levels: u32,
+    },
+    #[error(
+        "Image {} dimension of {size} exceeds the `{limit}` limit of the device ({max}).",
+        .label.as_deref().unwrap_or("<unlabelled>")
+    )]
+    ExceedsLimit {
+        label: Option<String>,
+        limit: &'static str,
+        size: u32,
+        max: u32,
+    },
+    #[error(transparent)]
+    OutOfBudget(#[from] OutOfBudget),
 }
 
-impl<'fw, P> ImgOps<'fw> for GpuImage<'fw, P>
-where
-    P: PixelInfo,
-{
-    fn as_binding_resource(&self) -> wgpu::BindingResource {
-        wgpu::BindingResource::TextureView(&self.full_view)
+#[derive(Error, Debug)]
+pub enum ImageCopyError {
+    #[error(
+        "Image {} ({}x{}) and image {} ({}x{}) dimensions differ.",
+        .src.as_deref().unwrap_or("<unlabelled>"), .src_size.0, .src_size.1,
+        .dst.as_deref().unwrap_or("<unlabelled>"), .dst_size.0, .dst_size.1
+    )]
+    SizeMismatch {
+        src: Option<String>,
+        dst: Option<String>,
+        src_size: (u32, u32),
+        dst_size: (u32, u32),
+    },
+    #[error(
+        "Region ({x}, {y}) {width}x{height} is out of bounds of image {}.",
+        .label.as_deref().unwrap_or("<unlabelled>")
+    )]
+    RegionOutOfBounds {
+        label: Option<String>,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+    #[error(
+        "Buffer {} is too small for image {} (required size {required} bytes, current size {current} bytes).",
+        .buffer.as_deref().unwrap_or("<unlabelled>"),
+        .image.as_deref().unwrap_or("<unlabelled>")
+    )]
+    BufferTooSmall {
+        buffer: Option<String>,
+        image: Option<String>,
+        required: u64,
+        current: u64,
+    },
+    #[error(
+        "Image {} format {format:?} cannot be written by compute kernels on this device.",
+        .label.as_deref().unwrap_or("<unlabelled>")
+    )]
+    UnsupportedFormat {
+        label: Option<String>,
+        format: wgpu::TextureFormat,
+    },
+    #[error(transparent)]
+    ShaderError(#[from] ShaderError),
+    #[error(transparent)]
+    OutOfBudget(#[from] OutOfBudget),
+}
+
+#[derive(Error, Debug)]
+pub enum ImageBindError {
+    #[error(
+        "Image {} format {format:?} does not support read-write storage access.",
+        .label.as_deref().unwrap_or("<unlabelled>")
+    )]
+    ReadWriteUnsupported {
+        label: Option<String>,
+        format: wgpu::TextureFormat,
+    },
+    #[error(
+        "Mip level {level} is out of bounds of image {} ({levels} levels).",
+        .label.as_deref().unwrap_or("<unlabelled>")
+    )]
+    MipLevelOutOfBounds {
+        label: Option<String>,
+        level: u32,
+        levels: u32,
+    },
+    #[error(
+        "Image {} was not created with storage access, so its mip levels cannot be bound for storage.",
+        .label.as_deref().unwrap_or("<unlabelled>")
+    )]
+    StorageUnsupported { label: Option<String> },
+}
+
+/// Returns the origin and extent of the `width` x `height` region at (`x`, `y`)
+/// if it fits inside an image of `size`.
+pub(crate) fn region(
+    size: wgpu::Extent3d,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+) -> Option<(wgpu::Origin3d, wgpu::Extent3d)> {
+    let fits_x = x.checked_add(width).is_some_and(|end| end <= size.width);
+    let fits_y = y.checked_add(height).is_some_and(|end| end <= size.height);
+
+    (fits_x && fits_y).then_some((
+        wgpu::Origin3d { x, y, z: 0 },
+        wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    ))
+}
+
+/// Returns the number of pixels of an image of `size`.
+///
+/// Computed in `u64` so large images cannot overflow, saturating at `usize::MAX`
+/// when the count does not fit in a `usize`.
+pub(crate) fn pixel_count(size: wgpu::Extent3d) -> usize {
+    let pixels = size.width as u64 * size.height as u64 * size.depth_or_array_layers as u64;
+    usize::try_from(pixels).unwrap_or(usize::MAX)
+}
+
+/// Returns the number of bytes of an image of `size` with pixels of `byte_size` bytes.
+pub(crate) fn byte_count(size: wgpu::Extent3d, byte_size: usize) -> usize {
+    pixel_count(size).saturating_mul(byte_size)
+}
+
+/// Location of the mip `level` of `texture` starting at `origin`.
+pub(crate) fn texture_copy(
+    texture: &wgpu::Texture,
+    level: u32,
+    origin: wgpu::Origin3d,
+) -> wgpu::ImageCopyTexture<'_> {
+    wgpu::ImageCopyTexture {
+        aspect: wgpu::TextureAspect::All,
+        mip_level: level,
+        origin,
+        texture,
     }
+}
 
-    fn as_gpu_texture(&self) -> &wgpu::Texture {
-        &self.texture
+/// Row size in bytes of a texture to buffer copy, padded to `wgpu::COPY_BYTES_PER_ROW_ALIGNMENT`.
+pub(crate) fn padded_bytes_per_row(unpadded_bytes_per_row: u32) -> u32 {
+    wgpu::util::align_to(unpadded_bytes_per_row, wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+}
+
+/// Checks that images of pixel format `P` can be created with `usage` on `fw`.
+fn check_image_format<P: PixelInfo>(
+    fw: &Framework,
+    usage: wgpu::TextureUsages,
+    label: Option<&str>,
+) -> Result<(), ImageInputError> {
+    if P::supports_usages(fw, usage) {
+        Ok(())
+    } else {
+        Err(ImageInputError::UnsupportedFormat {
+            label: label.map(str::to_owned),
+            format: P::wgpu_format(),
+            usage,
+        })
     }
+}
 
-    fn get_wgpu_extent3d(&self) -> wgpu::Extent3d {
-        self.size
+/// Checks that an image of `size` and `dimension` fits the limits of `fw`.
+pub(crate) fn check_image_size(
+    fw: &Framework,
+    size: wgpu::Extent3d,
+    dimension: wgpu::TextureDimension,
+    label: Option<&str>,
+) -> Result<(), ImageInputError> {
+    let limits = fw.limits();
+    let checks = match dimension {
+        wgpu::TextureDimension::D3 => [
+            (
+                "max_texture_dimension_3d",
+                size.width,
+                limits.max_texture_dimension_3d,
+            ),
+            (
+                "max_texture_dimension_3d",
+                size.height,
+                limits.max_texture_dimension_3d,
+            ),
+            (
+                "max_texture_dimension_3d",
+                size.depth_or_array_layers,
+                limits.max_texture_dimension_3d,
+            ),
+        ],
+        _ => [
+            (
+                "max_texture_dimension_2d",
+                size.width,
+                limits.max_texture_dimension_2d,
+            ),
+            (
+                "max_texture_dimension_2d",
+                size.height,
+                limits.max_texture_dimension_2d,
+            ),
+            (
+                "max_texture_array_layers",
+                size.depth_or_array_layers,
+                limits.max_texture_array_layers,
+            ),
+        ],
+    };
+
+    match checks.iter().copied().find(|&(_, size, max)| size > max) {
+        Some((limit, size, max)) => Err(ImageInputError::ExceedsLimit {
+            label: label.map(str::to_owned),
+            limit,
+            size,
+            max,
+        }),
+        None => Ok(()),
     }
+}
 
-    fn dimensions(&self) -> (u32, u32) {
-        (self.size.width, self.size.height)
+/// Downloads the `size` region of the `copy` texture location into `buf` through a staging
+/// buffer, stripping the row padding. Returns the number of bytes read.
+pub(crate) async fn download_region(
+    fw: &Framework,
+    copy: wgpu::ImageCopyTexture<'_>,
+    label: &str,
+    bytes_per_pixel: u32,
+    size: wgpu::Extent3d,
+    buf: &mut [u8],
+) -> Result<usize, ImageOutputError> {
+    let unpadded_bytes_per_row = size.width * bytes_per_pixel;
+    let padded_bytes_per_row = padded_bytes_per_row(unpadded_bytes_per_row);
+    let rows = size.height as u64 * size.depth_or_array_layers as u64;
+    let staging_size = padded_bytes_per_row as u64 * rows;
+
+    // Nothing to copy, and mapping an empty staging buffer is not allowed.
+    if staging_size == 0 {
+        return Ok(0);
     }
 
-    fn new(fw: &'fw crate::Framework, width: u32, height: u32) -> Self {
-        let size = wgpu::Extent3d {
-            width,
-            height,
-            depth_or_array_layers: 1,
+    let label = format!("{} (staging)", label);
+
+    let _allocation = fw.allocate(MemoryKind::Staging, staging_size, Some(&label))?;
+    let staging = fw.device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some(&label),
+        size: staging_size,
+        usage: wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = fw
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some(&label),
+        });
+
+    encoder.copy_texture_to_buffer(
+        copy,
+        wgpu::ImageCopyBuffer {
+            buffer: &staging,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_bytes_per_row),
+                rows_per_image: Some(size.height),
+            },
+        },
+        size,
+    );
+
+    fw.queue.submit(Some(encoder.finish()));
+
+    let download =
+        super::buffers::download_buffer(fw, &staging, 0..staging_size, Some(&label)).await?;
+
+    let bytes_read = download
+        .chunks(padded_bytes_per_row as usize)
+        .zip(buf.chunks_mut(unpadded_bytes_per_row as usize))
+        .map(|(src, dest)| {
+            dest.copy_from_slice(&src[0..unpadded_bytes_per_row as usize]);
+            dest.len()
+        })
+        .sum();
+
+    Ok(bytes_read)
+}
+
+/// Uploads tightly packed `data` into the `size` region of the `copy` texture location.
+pub(crate) fn upload_region(
+    fw: &Framework,
+    copy: wgpu::ImageCopyTexture<'_>,
+    label: &str,
+    bytes_per_pixel: u32,
+    size: wgpu::Extent3d,
+    data: &[u8],
+) {
+    fw.queue.write_texture(
+        copy,
+        data,
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(bytes_per_pixel * size.width),
+            rows_per_image: Some(size.height),
+        },
+        size,
+    );
+
+    let encoder = fw
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some(label) });
+
+    fw.queue.submit(Some(encoder.finish()));
+}
+
+/// Checks that a tightly packed `width` x `height` image fits in a buffer of `buffer_size` bytes.
+/// Returns the unpadded and padded bytes per row.
+fn flat_copy_layout(
+    image_label: Option<&str>,
+    buffer_label: Option<&str>,
+    buffer_size: u64,
+    bytes_per_pixel: u32,
+    size: wgpu::Extent3d,
+) -> Result<(u32, u32), ImageCopyError> {
+    let unpadded_bytes_per_row = size.width * bytes_per_pixel;
+    let padded_bytes_per_row = padded_bytes_per_row(unpadded_bytes_per_row);
+    let required = unpadded_bytes_per_row as u64 * size.height as u64;
+
+    if buffer_size < required {
+        return Err(ImageCopyError::BufferTooSmall {
+            buffer: buffer_label.map(str::to_owned),
+            image: image_label.map(str::to_owned),
+            required,
+            current: buffer_size,
+        });
+    }
+
+    Ok((unpadded_bytes_per_row, padded_bytes_per_row))
+}
+
+/// Staging buffers of a copy between an image and a tightly packed buffer whose rows need
+/// padding. `padded` holds the rows as texture copies lay them out and `packed` holds them
+/// tightly packed, rounded up to whole words so a kernel can repack them from one to the other.
+struct FlatStaging {
+    padded: wgpu::Buffer,
+    packed: wgpu::Buffer,
+    _allocation: memory::Allocation,
+}
+
+impl FlatStaging {
+    /// Creates the staging buffers of a `size` image with pixels of `bytes_per_pixel` bytes.
+    ///
+    /// `to_image` is `true` when the pixels are copied into the image.
+    fn new(
+        fw: &Framework,
+        label: &str,
+        bytes_per_pixel: u32,
+        size: wgpu::Extent3d,
+        to_image: bool,
+    ) -> Result<Self, ImageCopyError> {
+        let unpadded_bytes_per_row = size.width * bytes_per_pixel;
+        let padded_size = padded_bytes_per_row(unpadded_bytes_per_row) as u64 * size.height as u64;
+        let packed_size = wgpu::util::align_to(
+            unpadded_bytes_per_row as u64 * size.height as u64,
+            wgpu::COPY_BUFFER_ALIGNMENT,
+        );
+
+        let label = format!("{} (staging)", label);
+        let allocation =
+            fw.allocate(MemoryKind::Staging, padded_size + packed_size, Some(&label))?;
+
+        let (padded_usage, packed_usage) = if to_image {
+            (wgpu::BufferUsages::COPY_SRC, wgpu::BufferUsages::COPY_DST)
+        } else {
+            (wgpu::BufferUsages::COPY_DST, wgpu::BufferUsages::COPY_SRC)
+        };
+        let create = |size, usage| {
+            fw.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(&label),
+                size,
+                usage: usage | wgpu::BufferUsages::STORAGE,
+                mapped_at_creation: false,
+            })
         };
 
-        let format = P::wgpu_format();
+        Ok(Self {
+            padded: create(padded_size, padded_usage),
+            packed: create(packed_size, packed_usage),
+            _allocation: allocation,
+        })
+    }
+}
 
-        let texture = fw.device.create_texture(&wgpu::TextureDescriptor {
-            label: Some("GpuImage::new"),
-            size,
-            dimension: wgpu::TextureDimension::D2,
-            mip_level_count: 1,
-            sample_count: 1,
+/// Records the repacking of `rows` rows of `row_bytes` bytes from `src`, where they start
+/// every `src_stride` bytes, into `dst`, where they start every `dst_stride` bytes.
+///
+/// Both buffers are storage buffers of whole words holding every row. Rows are repacked
+/// in bands that fit the storage binding limits of `fw`.
+#[allow(clippy::too_many_arguments)]
+fn record_repack(
+    fw: &Framework,
+    encoder: &mut wgpu::CommandEncoder,
+    src: &wgpu::Buffer,
+    src_stride: u32,
+    dst: &wgpu::Buffer,
+    dst_stride: u32,
+    row_bytes: u32,
+    rows: u32,
+    label: &str,
+) -> Result<(), ImageCopyError> {
+    let kernel = ImageKernel::get(fw, ImageKernelKey::Repack, Some(label))?;
+    let params = fw
+        .device
+        .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(label),
+            contents: bytemuck::cast_slice(&[src_stride, dst_stride, row_bytes, 0]),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
+    // Bands start at rows whose offsets in both buffers are aligned for storage bindings.
+    // The alignment is a power of two, so is the row step aligning each stride.
+    let limits = fw.limits();
+    let alignment = limits.min_storage_buffer_offset_alignment;
+    let step = |stride: u32| alignment >> stride.trailing_zeros().min(alignment.trailing_zeros());
+    let band_step = step(src_stride).max(step(dst_stride));
+    let max_rows = limits.max_storage_buffer_binding_size / src_stride.max(dst_stride);
+    let band_rows = (max_rows / band_step * band_step).max(band_step);
+
+    // Band offsets are aligned and the buffers hold whole words, so bands can span whole words.
+    let binding = |buffer: &wgpu::Buffer, stride: u32, first: u32, count: u32| {
+        let offset = first as u64 * stride as u64;
+        let size = wgpu::util::align_to(count as u64 * stride as u64, wgpu::COPY_BUFFER_ALIGNMENT)
+            .min(buffer.size() - offset);
+
+        (offset, size)
+    };
+
+    for first in (0..rows).step_by(band_rows as usize) {
+        let count = band_rows.min(rows - first);
+        let (src_offset, src_size) = binding(src, src_stride, first, count);
+        let (dst_offset, dst_size) = binding(dst, dst_stride, first, count);
+
+        let groups = (dst_size / 4).div_ceil(REPACK_WORKGROUP_SIZE as u64) as u32;
+        let groups_x = groups.min(limits.max_compute_workgroups_per_dimension);
+
+        kernel.dispatch(
+            fw,
+            encoder,
+            &[
+                wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                    buffer: src,
+                    offset: src_offset,
+                    size: wgpu::BufferSize::new(src_size),
+                }),
+                wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                    buffer: dst,
+                    offset: dst_offset,
+                    size: wgpu::BufferSize::new(dst_size),
+                }),
+                params.as_entire_binding(),
+            ],
+            [groups_x, groups.div_ceil(groups_x)],
+            label,
+        );
+    }
+
+    Ok(())
+}
+
+/// Returns the byte offset in a tightly packed buffer, origin and width of the runs of pixels
+/// of a `width` pixels wide image that hold its bytes from `start` to `end`.
+///
+/// Texture copies of a single row need no row alignment, so these runs can be copied
+/// directly between the image and a buffer of any size.
+fn pixel_runs(
+    start: u64,
+    end: u64,
+    bytes_per_pixel: u32,
+    width: u32,
+) -> impl Iterator<Item = (u64, wgpu::Origin3d, u32)> {
+    let bytes_per_pixel = bytes_per_pixel as u64;
+    let width = width as u64;
+    let end = end / bytes_per_pixel;
+
+    std::iter::successors(Some(start / bytes_per_pixel), move |&pixel| {
+        Some(pixel + width - pixel % width)
+    })
+    .take_while(move |&pixel| pixel < end)
+    .map(move |pixel| {
+        let origin = wgpu::Origin3d {
+            x: (pixel % width) as u32,
+            y: (pixel / width) as u32,
+            z: 0,
+        };
+        let count = (width - pixel % width).min(end - pixel) as u32;
+
+        (pixel * bytes_per_pixel, origin, count)
+    })
+}
+
+/// WGSL scalar type returned by `textureLoad` or expected by `textureStore` for `sample`.
+fn wgsl_texel_scalar(sample: wgpu::TextureSampleType) -> &'static str {
+    match sample {
+        wgpu::TextureSampleType::Uint => "u32",
+        wgpu::TextureSampleType::Sint => "i32",
+        wgpu::TextureSampleType::Float { .. } | wgpu::TextureSampleType::Depth => "f32",
+    }
+}
+
+/// WGSL name of the storage texel `format`, if it can be declared as a storage image.
+fn wgsl_storage_format(format: wgpu::TextureFormat) -> Option<&'static str> {
+    use wgpu::TextureFormat as Tf;
+
+    Some(match format {
+        Tf::R8Unorm => "r8unorm",
+        Tf::R8Snorm => "r8snorm",
+        Tf::R8Uint => "r8uint",
+        Tf::R8Sint => "r8sint",
+        Tf::R16Unorm => "r16unorm",
+        Tf::R16Snorm => "r16snorm",
+        Tf::R16Uint => "r16uint",
+        Tf::R16Sint => "r16sint",
+        Tf::R16Float => "r16float",
+        Tf::Rg8Unorm => "rg8unorm",
+        Tf::Rg8Snorm => "rg8snorm",
+        Tf::Rg8Uint => "rg8uint",
+        Tf::Rg8Sint => "rg8sint",
+        Tf::R32Uint => "r32uint",
+        Tf::R32Sint => "r32sint",
+        Tf::R32Float => "r32float",
+        Tf::Rg16Unorm => "rg16unorm",
+        Tf::Rg16Snorm => "rg16snorm",
+        Tf::Rg16Uint => "rg16uint",
+        Tf::Rg16Sint => "rg16sint",
+        Tf::Rg16Float => "rg16float",
+        Tf::Rgba8Unorm => "rgba8unorm",
+        Tf::Rgba8Snorm => "rgba8snorm",
+        Tf::Rgba8Uint => "rgba8uint",
+        Tf::Rgba8Sint => "rgba8sint",
+        Tf::Bgra8Unorm => "bgra8unorm",
+        Tf::Rgb10a2Uint => "rgb10a2uint",
+        Tf::Rgb10a2Unorm => "rgb10a2unorm",
+        Tf::Rg11b10Float => "rg11b10float",
+        Tf::Rg32Uint => "rg32uint",
+        Tf::Rg32Sint => "rg32sint",
+        Tf::Rg32Float => "rg32float",
+        Tf::Rgba16Unorm => "rgba16unorm",
+        Tf::Rgba16Snorm => "rgba16snorm",
+        Tf::Rgba16Uint => "rgba16uint",
+        Tf::Rgba16Sint => "rgba16sint",
+        Tf::Rgba16Float => "rgba16float",
+        Tf::Rgba32Uint => "rgba32uint",
+        Tf::Rgba32Sint => "rgba32sint",
+        Tf::Rgba32Float => "rgba32float",
+        _ => return None,
+    })
+}
+
+/// Generates the WGSL source of the format-converting blit kernel from a `src`
+/// texture into a `dst` storage image of WGSL storage format `dst_format`.
+fn blit_shader_source(
+    src: wgpu::TextureSampleType,
+    dst: wgpu::TextureSampleType,
+    dst_format: &str,
+) -> String {
+    let src_scalar = wgsl_texel_scalar(src);
+    let dst_scalar = wgsl_texel_scalar(dst);
+
+    format!(
+        r#"@group(0) @binding(0)
+var src: texture_2d<{src_scalar}>;
+
+@group(0) @binding(1)
+var dst: texture_storage_2d<{dst_format}, write>;
+
+@compute @workgroup_size({BLIT_WORKGROUP_SIZE}, {BLIT_WORKGROUP_SIZE}, 1)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {{
+    let size = textureDimensions(dst);
+    if (id.x >= size.x || id.y >= size.y) {{
+        return;
+    }}
+
+    let texel = textureLoad(src, vec2<i32>(id.xy), 0);
+    textureStore(dst, vec2<i32>(id.xy), vec4<{dst_scalar}>(texel));
+}}
+"#
+    )
+}
+
+const BLIT_WORKGROUP_SIZE: u32 = 8;
+
+/// Generates the WGSL source of the repacking kernel, which copies rows of bytes between
+/// buffers with different row strides. Each invocation writes one word of `dst`.
+fn repack_shader_source() -> String {
+    format!(
+        r#"struct Strides {{
+    src: u32,
+    dst: u32,
+    row_bytes: u32,
+}}
+
+@group(0) @binding(0)
+var<storage, read> src: array<u32>;
+
+@group(0) @binding(1)
+var<storage, read_write> dst: array<u32>;
+
+@group(0) @binding(2)
+var<uniform> strides: Strides;
+
+@compute @workgroup_size({REPACK_WORKGROUP_SIZE}, 1, 1)
+fn main(
+    @builtin(global_invocation_id) id: vec3<u32>,
+    @builtin(num_workgroups) groups: vec3<u32>,
+) {{
+    let word = id.x + id.y * groups.x * {REPACK_WORKGROUP_SIZE}u;
+    if (word >= arrayLength(&dst)) {{
+        return;
+    }}
+
+    var value = 0u;
+    for (var i = 0u; i < 4u; i = i + 1u) {{
+        let byte = word * 4u + i;
+        let column = byte % strides.dst;
+        let offset = byte / strides.dst * strides.src + column;
+
+        if (column < strides.row_bytes && offset / 4u < arrayLength(&src)) {{
+            let texel_byte = (src[offset / 4u] >> (offset % 4u * 8u)) & 0xffu;
+            value = value | (texel_byte << (i * 8u));
+        }}
+    }}
+
+    dst[word] = value;
+}}
+"#
+    )
+}
+
+const REPACK_WORKGROUP_SIZE: u32 = 64;
+
+/// Internal image kernel, identified by what it does and the texel types it was generated for.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum ImageKernelKey {
+    Blit {
+        src: wgpu::TextureSampleType,
+        dst: wgpu::TextureSampleType,
+        format: wgpu::TextureFormat,
+    },
+    Mip {
+        sample: wgpu::TextureSampleType,
+        format: wgpu::TextureFormat,
+    },
+    Repack,
+}
+
+/// Pipeline of an internal image kernel.
+///
+/// Blit and mip kernels read a texture at binding 0 and write a storage image at binding 1.
+/// The repacking kernel reads a storage buffer at binding 0, writes one at binding 1 and reads
+/// its strides from a uniform buffer at binding 2.
+struct ImageKernel {
+    layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::ComputePipeline,
+}
+
+/// Internal image kernels of a [`Framework`], built on first use and shared by its clones.
+#[derive(Default)]
+pub(crate) struct ImageKernels(Mutex<HashMap<ImageKernelKey, Arc<ImageKernel>>>);
+
+impl ImageKernel {
+    /// Returns the kernel of `key`, building it the first time it is used with `fw`.
+    ///
+    /// `label` is the image reported by errors.
+    fn get(
+        fw: &Framework,
+        key: ImageKernelKey,
+        label: Option<&str>,
+    ) -> Result<Arc<Self>, ImageCopyError> {
+        // The cache is always left consistent, even by a panicking thread.
+        let mut kernels = fw
+            .image_kernels
+            .0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        if let Some(kernel) = kernels.get(&key) {
+            return Ok(Arc::clone(kernel));
+        }
+
+        let kernel = Arc::new(Self::build(fw, key, label)?);
+        kernels.insert(key, Arc::clone(&kernel));
+
+        Ok(kernel)
+    }
+
+    fn build(
+        fw: &Framework,
+        key: ImageKernelKey,
+        label: Option<&str>,
+    ) -> Result<Self, ImageCopyError> {
+        let unsupported = |format| ImageCopyError::UnsupportedFormat {
+            label: label.map(str::to_owned),
             format,
-            usage: GPU_IMAGE_USAGES,
-            view_formats: &[format],
+        };
+
+        let (name, source, entries) = match key {
+            ImageKernelKey::Blit { src, dst, format } => {
+                let wgsl_format = wgsl_storage_format(format).ok_or_else(|| unsupported(format))?;
+                (
+                    "GpuImage::blit_to",
+                    blit_shader_source(src, dst, wgsl_format),
+                    image_kernel_entries(src, format),
+                )
+            }
+            ImageKernelKey::Mip { sample, format } => {
+                let wgsl_format = wgsl_storage_format(format).ok_or_else(|| unsupported(format))?;
+                (
+                    "GpuConstImage::generate_mipmaps",
+                    mip_shader_source(sample, wgsl_format),
+                    image_kernel_entries(sample, format),
+                )
+            }
+            ImageKernelKey::Repack => (
+                "image row repacking",
+                repack_shader_source(),
+                vec![
+                    buffer_entry(0, wgpu::BufferBindingType::Storage { read_only: true }),
+                    buffer_entry(1, wgpu::BufferBindingType::Storage { read_only: false }),
+                    buffer_entry(2, wgpu::BufferBindingType::Uniform),
+                ],
+            ),
+        };
+
+        let shader = crate::Shader::from_wgsl_string(fw, source, Some(name))?;
+
+        let layout = fw
+            .device
+            .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some(name),
+                entries: &entries,
+            });
+
+        let pipeline_layout = fw
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some(name),
+                bind_group_layouts: &[&layout],
+                push_constant_ranges: &[],
+            });
+
+        let pipeline = fw
+            .device
+            .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(name),
+                layout: Some(&pipeline_layout),
+                module: &shader.module,
+                entry_point: "main",
+            });
+
+        Ok(Self { layout, pipeline })
+    }
+
+    /// Records a pass of the kernel writing the `size` storage image `dst` from `src`.
+    fn record(
+        &self,
+        fw: &Framework,
+        encoder: &mut wgpu::CommandEncoder,
+        src: &wgpu::TextureView,
+        dst: &wgpu::TextureView,
+        size: wgpu::Extent3d,
+        label: &str,
+    ) {
+        self.dispatch(
+            fw,
+            encoder,
+            &[
+                wgpu::BindingResource::TextureView(src),
+                wgpu::BindingResource::TextureView(dst),
+            ],
+            [
+                size.width.div_ceil(BLIT_WORKGROUP_SIZE),
+                size.height.div_ceil(BLIT_WORKGROUP_SIZE),
+            ],
+            label,
+        );
+    }
+
+    /// Records a pass of the kernel dispatching `workgroups` with `resources` bound in order.
+    fn dispatch(
+        &self,
+        fw: &Framework,
+        encoder: &mut wgpu::CommandEncoder,
+        resources: &[wgpu::BindingResource<'_>],
+        workgroups: [u32; 2],
+        label: &str,
+    ) {
+        let entries = resources
+            .iter()
+            .enumerate()
+            .map(|(binding, resource)| wgpu::BindGroupEntry {
+                binding: binding as u32,
+                resource: resource.clone(),
+            })
+            .collect::<Vec<_>>();
+        let bind_group = fw.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(label),
+            layout: &self.layout,
+            entries: &entries,
         });
 
-        let full_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some(label),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.dispatch_workgroups(workgroups[0], workgroups[1], 1);
+    }
+}
 
+/// Layout entries of a blit or mip kernel reading `src` texels and writing `format` texels.
+fn image_kernel_entries(
+    src: wgpu::TextureSampleType,
+    format: wgpu::TextureFormat,
+) -> Vec<wgpu::BindGroupLayoutEntry> {
+    vec![
+        wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Texture {
+                sample_type: src,
+                multisampled: false,
+                view_dimension: wgpu::TextureViewDimension::D2,
+            },
+            count: None,
+        },
+        wgpu::BindGroupLayoutEntry {
+            binding: 1,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::StorageTexture {
+                access: wgpu::StorageTextureAccess::WriteOnly,
+                format,
+                view_dimension: wgpu::TextureViewDimension::D2,
+            },
+            count: None,
+        },
+    ]
+}
+
+/// Layout entry of a buffer of type `ty` at `binding` of the repacking kernel.
+fn buffer_entry(binding: u32, ty: wgpu::BufferBindingType) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::COMPUTE,
+        ty: wgpu::BindingType::Buffer {
+            ty,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    }
+}
+
+/// Storage image of the size and format of a [`GpuConstImage`], which
+/// [`GpuImage::blit_to`] writes before copying it into the [`GpuConstImage`].
+pub(crate) struct BlitTarget {
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    _allocation: memory::Allocation,
+}
+
+/// Generates the WGSL source of the mip generation kernel, which downsamples a
+/// `src` mip level into the next `dst` level of WGSL storage format `format` with a 2x2 box filter.
+fn mip_shader_source(sample: wgpu::TextureSampleType, format: &str) -> String {
+    let scalar = wgsl_texel_scalar(sample);
+    let four = match sample {
+        wgpu::TextureSampleType::Uint => "4u",
+        wgpu::TextureSampleType::Sint => "4i",
+        wgpu::TextureSampleType::Float { .. } | wgpu::TextureSampleType::Depth => "4.0",
+    };
+
+    format!(
+        r#"@group(0) @binding(0)
+var src: texture_2d<{scalar}>;
+
+@group(0) @binding(1)
+var dst: texture_storage_2d<{format}, write>;
+
+@compute @workgroup_size({BLIT_WORKGROUP_SIZE}, {BLIT_WORKGROUP_SIZE}, 1)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {{
+    let size = textureDimensions(dst);
+    if (id.x >= size.x || id.y >= size.y) {{
+        return;
+    }}
+
+    let last = vec2<i32>(textureDimensions(src)) - vec2<i32>(1, 1);
+    let base = vec2<i32>(id.xy) * 2;
+
+    let sum = textureLoad(src, min(base, last), 0)
+        + textureLoad(src, min(base + vec2<i32>(1, 0), last), 0)
+        + textureLoad(src, min(base + vec2<i32>(0, 1), last), 0)
+        + textureLoad(src, min(base + vec2<i32>(1, 1), last), 0);
+
+    textureStore(dst, vec2<i32>(id.xy), sum / vec4<{scalar}>({four}));
+}}
+"#
+    )
+}
+
+pub(crate) mod sealed {
+    /// Image types that can be created by an [`ImageBuilder`](super::ImageBuilder).
+    pub trait BuildableImage {
+        /// Wraps `texture`, keeping the `allocation` reserved for it alive.
+        fn from_texture(
+            fw: crate::Framework,
+            texture: wgpu::Texture,
+            size: wgpu::Extent3d,
+            label: Option<String>,
+            allocation: crate::memory::Allocation,
+        ) -> Self;
+    }
+}
+
+/// Builder of GPU images with custom label and initial data.
+///
+/// Created by [`GpuImage::builder`], [`GpuConstImage::builder`] or the
+/// `builder` functions of the 3D and array images.
+///
+/// # Example
+/// ```no_run
+/// # use gpgpu::{primitives::pixels::Rgba8Uint, *};
+/// # let fw = Framework::default();
+/// let img = GpuImage::<Rgba8Uint>::builder(&fw, 1920, 1080)
+///     .label("Blur output")
+///     .build()
+///     .unwrap();
+/// ```
+pub struct ImageBuilder<'a, P, I> {
+    fw: Framework,
+    label: Option<String>,
+    size: wgpu::Extent3d,
+    dimension: wgpu::TextureDimension,
+    usage: wgpu::TextureUsages,
+    mip_level_count: u32,
+    data: Option<&'a [u8]>,
+    marker: PhantomData<(P, I)>,
+}
+
+impl<'a, P, I> ImageBuilder<'a, P, I>
+where
+    P: PixelInfo,
+    I: sealed::BuildableImage,
+{
+    pub(crate) fn new(
+        fw: Framework,
+        size: wgpu::Extent3d,
+        dimension: wgpu::TextureDimension,
+        usage: wgpu::TextureUsages,
+    ) -> Self {
         Self {
             fw,
-            texture,
+            label: None,
             size,
-            full_view,
-            pixel: PhantomData,
+            dimension,
+            usage,
+            mip_level_count: 1,
+            data: None,
+            marker: PhantomData,
         }
     }
 
-    fn from_bytes(fw: &'fw crate::Framework, data: &[u8], width: u32, height: u32) -> Self {
-        let size = wgpu::Extent3d {
-            width,
-            height,
-            depth_or_array_layers: 1,
-        };
+    /// Sets the debug label of the image.
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
 
+    /// Sets the initial data of the image. It must fit the image perfectly.
+    pub fn data(mut self, data: &'a [u8]) -> Self {
+        self.data = Some(data);
+        self
+    }
+
+    /// Sets the initial pixels of the image. They must fit the image perfectly.
+    pub fn pixels(mut self, pixels: &'a [P::Pixel]) -> Self {
+        self.data = Some(bytemuck::cast_slice(pixels));
+        self
+    }
+
+    /// Creates the image.
+    ///
+    /// Fails if the pixel format cannot be used with the image usages on this device
+    /// (e.g. storage access on sRGB formats or 16 bit normalised formats without
+    /// `wgpu::Features::TEXTURE_FORMAT_16BIT_NORM`).
+    pub fn build(self) -> Result<I, ImageInputError> {
         let format = P::wgpu_format();
 
-        let texture = fw.device.create_texture_with_data(
-            &fw.queue,
-            &wgpu::TextureDescriptor {
-                label: Some("GpuImage::from_bytes"),
-                size,
-                dimension: wgpu::TextureDimension::D2,
-                mip_level_count: 1,
-                sample_count: 1,
-                format,
-                usage: GPU_IMAGE_USAGES,
-                view_formats: &[format],
-            },
-            wgpu::util::TextureDataOrder::LayerMajor,
-            data,
-        );
+        check_image_format::<P>(&self.fw, self.usage, self.label.as_deref())?;
 
-        let full_view = texture.create_view(&Default::default());
+        // Mip chains are generated by a compute kernel writing every level as a storage image.
+        let usage = if self.mip_level_count > 1 && P::supports_storage(&self.fw) {
+            self.usage | wgpu::TextureUsages::STORAGE_BINDING
+        } else {
+            self.usage
+        };
+
+        check_image_size(&self.fw, self.size, self.dimension, self.label.as_deref())?;
+        let allocation = self.fw.allocate(
+            MemoryKind::Image,
+            memory::image_size(format, self.size, self.dimension, self.mip_level_count),
+            self.label.as_deref(),
+        )?;
+
+        let desc = wgpu::TextureDescriptor {
+            label: self.label.as_deref(),
+            size: self.size,
+            dimension: self.dimension,
+            mip_level_count: self.mip_level_count,
+            sample_count: 1,
+            format,
+            usage,
+            view_formats: &[format],
+        };
+
+        let texture = match self.data {
+            Some(data) => {
+                let required = byte_count(self.size, P::byte_size());
+
+                if data.len() != required {
+                    return Err(ImageInputError::SizeMismatch {
+                        label: self.label,
+                        required,
+                        current: data.len(),
+                    });
+                }
+
+                if self.mip_level_count == 1 {
+                    self.fw.device.create_texture_with_data(
+                        &self.fw.queue,
+                        &desc,
+                        wgpu::util::TextureDataOrder::LayerMajor,
+                        data,
+                    )
+                } else {
+                    let texture = self.fw.device.create_texture(&desc);
+                    upload_region(
+                        &self.fw,
+                        texture_copy(&texture, 0, wgpu::Origin3d::ZERO),
+                        self.label.as_deref().unwrap_or("ImageBuilder::build"),
+                        P::byte_size() as u32,
+                        self.size,
+                        data,
+                    );
+                    texture
+                }
+            }
+            None => self.fw.device.create_texture(&desc),
+        };
+
+        Ok(I::from_texture(
+            self.fw, texture, self.size, self.label, allocation,
+        ))
+    }
+}
+
+impl<'a, P> ImageBuilder<'a, P, GpuConstImage<P>>
+where
+    P: PixelInfo,
+{
+    /// Sets the number of mip levels of the image, clamped to a full mip chain.
+    /// Initial data, if any, fills the level 0.
+    ///
+    /// Levels can be generated with [`GpuConstImage::generate_mipmaps`] when the
+    /// pixel format supports storage access.
+    pub fn mip_level_count(mut self, count: u32) -> Self {
+        self.mip_level_count = count.clamp(1, self.size.max_mips(self.dimension));
+        self
+    }
+
+    /// Sets the number of mip levels of the image to a full mip chain, down to 1x1.
+    pub fn full_mip_chain(mut self) -> Self {
+        self.mip_level_count = self.size.max_mips(self.dimension);
+        self
+    }
+}
+
+impl<P> sealed::BuildableImage for GpuImage<P>
+where
+    P: PixelInfo,
+{
+    fn from_texture(
+        fw: Framework,
+        texture: wgpu::Texture,
+        size: wgpu::Extent3d,
+        label: Option<String>,
+        allocation: memory::Allocation,
+    ) -> Self {
+        let full_view = texture.create_view(&wgpu::TextureViewDescriptor {
+            label: label.as_deref(),
+            ..Default::default()
+        });
 
         Self {
             fw,
             texture,
             size,
             full_view,
+            label,
+            _allocation: allocation,
             pixel: PhantomData,
         }
     }
+}
+
+impl<P> ImgOps for GpuImage<P>
+where
+    P: PixelInfo,
+{
+    fn as_binding_resource(&self) -> wgpu::BindingResource<'_> {
+        wgpu::BindingResource::TextureView(&self.full_view)
+    }
+
+    fn as_gpu_texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+
+    fn get_wgpu_extent3d(&self) -> wgpu::Extent3d {
+        self.size
+    }
+
+    fn dimensions(&self) -> (u32, u32) {
+        (self.size.width, self.size.height)
+    }
+
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
 
-    /// Constructs an image from a [`wgpu::Texture`] and its [`wgpu::Extent3d`].
+    fn new(fw: &Framework, width: u32, height: u32) -> Self {
+        Self::try_new(fw, width, height).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn try_new(fw: &Framework, width: u32, height: u32) -> Result<Self, ImageInputError> {
+        Self::builder(fw, width, height).build()
+    }
+
+    fn from_bytes(fw: &Framework, data: &[u8], width: u32, height: u32) -> Self {
+        Self::try_from_bytes(fw, data, width, height).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn try_from_bytes(
+        fw: &Framework,
+        data: &[u8],
+        width: u32,
+        height: u32,
+    ) -> Result<Self, ImageInputError> {
+        Self::builder(fw, width, height).data(data).build()
+    }
+
+    /// Constructs an image from a [`wgpu::Texture`], its [`wgpu::Extent3d`] and a debug `label`.
     ///
     /// # Safety
     /// If any of the following conditions are not satisfied, the image will
     /// panic at any time during its usage.
     /// - `texture` needs to be `wgpu::TextureUsages::STORAGE_BINDING`, `wgpu::TextureUsages::COPY_SRC`,
-    ///   and `wgpu::TextureUsages::COPY_DST`` usable.
+    ///   and `wgpu::TextureUsages::COPY_DST` usable. `wgpu::TextureUsages::TEXTURE_BINDING` is also
+    ///   required to bind it with [`DescriptorSet::bind_image_as_texture`](crate::DescriptorSet::bind_image_as_texture).
     /// - `T` needs to be the exact same codification `texture` is.
     /// - `dimensions` needs to have the exact `width` and `height` of `texture` and `depth_or_array_layers = 1`
-    fn from_gpu_parts(
-        fw: &'fw crate::Framework,
+    fn from_labelled_gpu_parts(
+        fw: &Framework,
         texture: wgpu::Texture,
         dimensions: wgpu::Extent3d,
+        label: Option<String>,
     ) -> Self {
-        let full_view = texture.create_view(&Default::default());
+        let allocation = fw.track(
+            MemoryKind::Image,
+            memory::texture_size(&texture),
+            label.as_deref(),
+        );
 
-        Self {
-            fw,
+        <Self as sealed::BuildableImage>::from_texture(
+            fw.clone(),
             texture,
-            size: dimensions,
-            full_view,
-            pixel: PhantomData,
-        }
+            dimensions,
+            label,
+            allocation,
+        )
     }
 
     fn into_gpu_parts(self) -> (wgpu::Texture, wgpu::Extent3d) {
         (self.texture, self.size)
     }
-}
+}
+
+impl<P> GpuImage<P>
+where
+    P: PixelInfo,
+{
+    /// Creates an [`ImageBuilder`] of a [`GpuImage`] with the desired `width` and `height`.
+    pub fn builder<'a>(fw: &Framework, width: u32, height: u32) -> ImageBuilder<'a, P, Self> {
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+
+        ImageBuilder::new(
+            fw.clone(),
+            size,
+            wgpu::TextureDimension::D2,
+            GPU_IMAGE_USAGES,
+        )
+    }
+
+    /// Constructs a new [`GpuImage`] of `width` x `height` from `pixels`, laid out row by row.
+    pub fn from_pixels(fw: &Framework, pixels: &[P::Pixel], width: u32, height: u32) -> Self {
+        Self::from_bytes(fw, bytemuck::cast_slice(pixels), width, height)
+    }
+
+    /// Returns `true` if the image can be bound as a read-write storage image,
+    /// which depends on the pixel format capabilities of the adapter.
+    pub fn supports_read_write(&self) -> bool {
+        self.fw
+            .texture_format_features(P::wgpu_format())
+            .flags
+            .contains(wgpu::TextureFormatFeatureFlags::STORAGE_READ_WRITE)
+    }
+
+    /// Copies this [`GpuImage`] into `dst` on the GPU, without going through host memory.
+    ///
+    /// Both images must have the same dimensions.
+    pub fn copy_to(&self, dst: &GpuConstImage<P>) -> Result<(), ImageCopyError> {
+        if self.size != dst.size {
+            return Err(ImageCopyError::SizeMismatch {
+                src: self.label.clone(),
+                dst: dst.label.clone(),
+                src_size: self.dimensions(),
+                dst_size: dst.dimensions(),
+            });
+        }
+
+        let (width, height) = self.dimensions();
+        self.copy_region_to(dst, (0, 0), (0, 0), (width, height))
+    }
+
+    /// Copies the `size` region at `src_origin` of this [`GpuImage`] into `dst` at `dst_origin`
+    /// on the GPU, without going through host memory.
+    ///
+    /// Origins are `(x, y)` pairs and `size` is a `(width, height)` pair.
+    pub fn copy_region_to(
+        &self,
+        dst: &GpuConstImage<P>,
+        src_origin: (u32, u32),
+        dst_origin: (u32, u32),
+        size: (u32, u32),
+    ) -> Result<(), ImageCopyError> {
+        let (width, height) = size;
+
+        let (src_origin, extent) = region(self.size, src_origin.0, src_origin.1, width, height)
+            .ok_or_else(|| ImageCopyError::RegionOutOfBounds {
+                label: self.label.clone(),
+                x: src_origin.0,
+                y: src_origin.1,
+                width,
+                height,
+            })?;
+        let (dst_origin, _) = region(dst.size, dst_origin.0, dst_origin.1, width, height)
+            .ok_or_else(|| ImageCopyError::RegionOutOfBounds {
+                label: dst.label.clone(),
+                x: dst_origin.0,
+                y: dst_origin.1,
+                width,
+                height,
+            })?;
+
+        let mut encoder = self
+            .fw
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some(self.label.as_deref().unwrap_or("GpuImage::copy_to")),
+            });
+
+        encoder.copy_texture_to_texture(
+            wgpu::ImageCopyTexture {
+                aspect: wgpu::TextureAspect::All,
+                mip_level: 0,
+                origin: src_origin,
+                texture: &self.texture,
+            },
+            wgpu::ImageCopyTexture {
+                aspect: wgpu::TextureAspect::All,
+                mip_level: 0,
+                origin: dst_origin,
+                texture: &dst.texture,
+            },
+            extent,
+        );
+
+        self.fw.queue.submit(Some(encoder.finish()));
+
+        Ok(())
+    }
+
+    /// Copies this [`GpuImage`] into `dst` converting its pixel format on the GPU.
+    ///
+    /// The conversion is done texel by texel by an internal compute kernel, using
+    /// WGSL numerical conversions between the shader-side texel types
+    /// (e.g. an [`Rgba8UintNorm`](super::pixels::Rgba8UintNorm) texel of `1.0` is
+    /// written as `1` into an [`Rgba32Uint`](super::pixels::Rgba32Uint) image).
+    /// The kernel is built once per pair of formats and reused by every [`Framework`] clone.
+    /// The first blit into `dst` also allocates a storage image of its size, kept along
+    /// with it for the next ones.
+    ///
+    /// Both images must have the same dimensions and `Q` must support storage access.
+    pub fn blit_to<Q>(&self, dst: &GpuConstImage<Q>) -> Result<(), ImageCopyError>
+    where
+        Q: PixelInfo,
+    {
+        if self.size != dst.size {
+            return Err(ImageCopyError::SizeMismatch {
+                src: self.label.clone(),
+                dst: dst.label.clone(),
+                src_size: self.dimensions(),
+                dst_size: dst.dimensions(),
+            });
+        }
+
+        let kernel = self.blit_kernel::<Q>(dst.label.as_deref())?;
+        let target = dst.blit_target()?;
+
+        let label = dst.label.as_deref().unwrap_or("GpuImage::blit_to");
+        let mut encoder = self
+            .fw
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some(label) });
+
+        kernel.record(
+            &self.fw,
+            &mut encoder,
+            &self.full_view,
+            &target.view,
+            self.size,
+            label,
+        );
+        encoder.copy_texture_to_texture(
+            target.texture.as_image_copy(),
+            dst.texture.as_image_copy(),
+            self.size,
+        );
+
+        self.fw.queue.submit(Some(encoder.finish()));
+
+        Ok(())
+    }
+
+    /// Copies this [`GpuImage`] into the storage image `dst` converting its pixel format on the GPU.
+    ///
+    /// See [`GpuImage::blit_to`] for the conversion rules.
+    pub fn blit_to_image<Q>(&self, dst: &GpuImage<Q>) -> Result<(), ImageCopyError>
+    where
+        Q: PixelInfo,
+    {
+        if self.size != dst.size {
+            return Err(ImageCopyError::SizeMismatch {
+                src: self.label.clone(),
+                dst: dst.label.clone(),
+                src_size: self.dimensions(),
+                dst_size: dst.dimensions(),
+            });
+        }
+
+        let kernel = self.blit_kernel::<Q>(dst.label.as_deref())?;
+
+        let label = dst.label.as_deref().unwrap_or("GpuImage::blit_to");
+        let mut encoder = self
+            .fw
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some(label) });
+
+        kernel.record(
+            &self.fw,
+            &mut encoder,
+            &self.full_view,
+            &dst.full_view,
+            self.size,
+            label,
+        );
+
+        self.fw.queue.submit(Some(encoder.finish()));
+
+        Ok(())
+    }
+
+    /// Returns the kernel converting the pixels of this image into `Q` storage images.
+    ///
+    /// `dst` is the label of the destination image, reported by errors.
+    fn blit_kernel<Q: PixelInfo>(
+        &self,
+        dst: Option<&str>,
+    ) -> Result<Arc<ImageKernel>, ImageCopyError> {
+        if !Q::supports_storage(&self.fw) {
+            return Err(ImageCopyError::UnsupportedFormat {
+                label: dst.map(str::to_owned),
+                format: Q::wgpu_format(),
+            });
+        }
+
+        ImageKernel::get(
+            &self.fw,
+            ImageKernelKey::Blit {
+                src: P::wgpu_texture_sample(),
+                dst: Q::wgpu_texture_sample(),
+                format: Q::wgpu_format(),
+            },
+            dst,
+        )
+    }
+
+    /// Copies the pixels of this [`GpuImage`] into `dst` on the GPU, row by row and
+    /// tightly packed, so the image can be processed as a flat array.
+    ///
+    /// `dst` must be at least `width * height * P::byte_size()` bytes long. When the rows
+    /// need padding to satisfy `wgpu::COPY_BYTES_PER_ROW_ALIGNMENT`, the image is copied into
+    /// a GPU staging buffer and repacked by a compute kernel.
+    pub fn copy_to_buffer<T>(&self, dst: &GpuBuffer<T>) -> Result<(), ImageCopyError>
+    where
+        T: bytemuck::Pod,
+    {
+        let (unpadded_bytes_per_row, padded_bytes_per_row) = flat_copy_layout(
+            self.label.as_deref(),
+            dst.label(),
+            dst.size(),
+            P::byte_size() as u32,
+            self.size,
+        )?;
+
+        let label = self.label.as_deref().unwrap_or("GpuImage::copy_to_buffer");
+
+        let mut encoder = self
+            .fw
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some(label) });
+
+        if unpadded_bytes_per_row == padded_bytes_per_row {
+            encoder.copy_texture_to_buffer(
+                self.texture.as_image_copy(),
+                wgpu::ImageCopyBuffer {
+                    buffer: dst.as_gpu_buffer(),
+                    layout: wgpu::ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: Some(padded_bytes_per_row),
+                        rows_per_image: Some(self.size.height),
+                    },
+                },
+                self.size,
+            );
+        } else {
+            let staging =
+                FlatStaging::new(&self.fw, label, P::byte_size() as u32, self.size, false)?;
+
+            encoder.copy_texture_to_buffer(
+                self.texture.as_image_copy(),
+                wgpu::ImageCopyBuffer {
+                    buffer: &staging.padded,
+                    layout: wgpu::ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: Some(padded_bytes_per_row),
+                        rows_per_image: Some(self.size.height),
+                    },
+                },
+                self.size,
+            );
+
+            record_repack(
+                &self.fw,
+                &mut encoder,
+                &staging.padded,
+                padded_bytes_per_row,
+                &staging.packed,
+                unpadded_bytes_per_row,
+                unpadded_bytes_per_row,
+                self.size.height,
+                label,
+            )?;
+
+            // Buffer copies move whole words: the last bytes are copied from the image.
+            let required = unpadded_bytes_per_row as u64 * self.size.height as u64;
+            let aligned = required / wgpu::COPY_BUFFER_ALIGNMENT * wgpu::COPY_BUFFER_ALIGNMENT;
+            if aligned > 0 {
+                encoder.copy_buffer_to_buffer(&staging.packed, 0, dst.as_gpu_buffer(), 0, aligned);
+            }
+
+            for (offset, origin, width) in
+                pixel_runs(aligned, required, P::byte_size() as u32, self.size.width)
+            {
+                encoder.copy_texture_to_buffer(
+                    texture_copy(&self.texture, 0, origin),
+                    wgpu::ImageCopyBuffer {
+                        buffer: dst.as_gpu_buffer(),
+                        layout: wgpu::ImageDataLayout {
+                            offset,
+                            bytes_per_row: None,
+                            rows_per_image: None,
+                        },
+                    },
+                    wgpu::Extent3d {
+                        width,
+                        height: 1,
+                        depth_or_array_layers: 1,
+                    },
+                );
+            }
+        }
+
+        self.fw.queue.submit(Some(encoder.finish()));
+
+        Ok(())
+    }
 
-impl<'fw, P> GpuImage<'fw, P>
-where
-    P: PixelInfo,
-{
     /// Pulls some elements from the [`GpuImage`] into `buf`, returning how many pixels were read.
     pub async fn read(&self, buf: &mut [u8]) -> Result<usize, ImageOutputError> {
-        let (width, height) = self.dimensions();
-
-        let img_bytes = (width * height) as usize * P::byte_size();
+        let img_bytes = byte_count(self.size, P::byte_size());
 
         if buf.len() < img_bytes {
             return Err(ImageOutputError::BufferTooSmall {
+                label: self.label.clone(),
                 required: img_bytes,
                 current: buf.len(),
             });
         }
 
-        let bytes_per_pixel = P::byte_size() as u32;
-        let unpadded_bytes_per_row = self.size.width * bytes_per_pixel;
-        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
-        let padded_bytes_per_row_padding = (align - unpadded_bytes_per_row % align) % align;
-        let padded_bytes_per_row = unpadded_bytes_per_row + padded_bytes_per_row_padding;
-
-        let staging_size = (padded_bytes_per_row * self.size.height) as usize;
-
-        let staging = self.fw.device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("GpuImage::read"),
-            size: staging_size as u64,
-            usage: wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
-
-        let mut encoder = self
-            .fw
-            .device
-            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                label: Some("GpuImage::read"),
-            });
-
-        let copy_texture = wgpu::ImageCopyTexture {
-            aspect: wgpu::TextureAspect::All,
-            mip_level: 0,
-            origin: wgpu::Origin3d::ZERO,
-            texture: &self.texture,
-        };
-
-        let copy_buffer = wgpu::ImageCopyBuffer {
-            buffer: &staging,
-            layout: wgpu::ImageDataLayout {
-                offset: 0,
-                bytes_per_row: Option::Some(padded_bytes_per_row),
-                rows_per_image: None,
-            },
-        };
-
-        encoder.copy_texture_to_buffer(copy_texture, copy_buffer, self.size);
-
-        self.fw.queue.submit(Some(encoder.finish()));
+        let label = self.label.as_deref().unwrap_or("GpuImage::read");
 
-        let (tx, rx) = futures::channel::oneshot::channel();
-        wgpu::util::DownloadBuffer::read_buffer(
-            &self.fw.device,
-            &self.fw.queue,
-            &staging.slice(..),
-            move |result| {
-                tx.send(result)
-                    .unwrap_or_else(|_| panic!("Failed to download buffer."));
-            },
-        );
-        let download = rx.await.unwrap().unwrap();
-
-        let bytes_read: usize = download
-            .chunks(padded_bytes_per_row as usize)
-            .zip(buf.chunks_mut(unpadded_bytes_per_row as usize))
-            .map(|(src, dest)| {
-                dest.copy_from_slice(&src[0..unpadded_bytes_per_row as usize]);
-                dest.len()
-            })
-            .sum();
+        let bytes_read = download_region(
+            &self.fw,
+            texture_copy(&self.texture, 0, wgpu::Origin3d::ZERO),
+            label,
+            P::byte_size() as u32,
+            self.size,
+            buf,
+        )
+        .await?;
 
         Ok(bytes_read / P::byte_size())
     }
 
     /// Pulls all the pixels from the [`GpuImage`] into a [`Vec`].
     pub async fn read_vec(&self) -> Result<Vec<u8>, ImageOutputError> {
-        let (width, height) = self.dimensions();
-        let img_pixels = (width * height) as usize * P::byte_size();
-
-        let mut buf = vec![0u8; img_pixels];
+        let mut buf = vec![0u8; byte_count(self.size, P::byte_size())];
         self.read(&mut buf).await?;
 
         Ok(buf)
@@ -248,12 +1694,132 @@ where
 
     /// Blocking version of `GpuImage::read()`.
     pub fn read_blocking(&self, buf: &mut [u8]) -> Result<usize, ImageOutputError> {
-        futures::executor::block_on(self.read(buf))
+        self.fw.block_on(self.read(buf))
     }
 
     /// Blocking version of `GpuImage::read_vec()`.
     pub fn read_vec_blocking(&self) -> Result<Vec<u8>, ImageOutputError> {
-        futures::executor::block_on(self.read_vec())
+        self.fw.block_on(self.read_vec())
+    }
+
+    /// Pulls some pixels from the [`GpuImage`] into `buf`, returning how many pixels were read.
+    pub async fn read_pixels(&self, buf: &mut [P::Pixel]) -> Result<usize, ImageOutputError> {
+        self.read(bytemuck::cast_slice_mut(buf)).await
+    }
+
+    /// Pulls all the pixels from the [`GpuImage`] into a [`Vec`] of pixels.
+    pub async fn read_pixels_vec(&self) -> Result<Vec<P::Pixel>, ImageOutputError> {
+        let mut buf = vec![P::Pixel::zeroed(); pixel_count(self.size)];
+        self.read_pixels(&mut buf).await?;
+
+        Ok(buf)
+    }
+
+    /// Blocking version of `GpuImage::read_pixels()`.
+    pub fn read_pixels_blocking(&self, buf: &mut [P::Pixel]) -> Result<usize, ImageOutputError> {
+        self.fw.block_on(self.read_pixels(buf))
+    }
+
+    /// Blocking version of `GpuImage::read_pixels_vec()`.
+    pub fn read_pixels_vec_blocking(&self) -> Result<Vec<P::Pixel>, ImageOutputError> {
+        self.fw.block_on(self.read_pixels_vec())
+    }
+
+    /// Writes pixels into this [`GpuImage`], returning how many pixels were written.
+    /// The operation is instantly offloaded.
+    ///
+    /// Typed version of [`GpuImage::write`].
+    pub fn write_pixels(&self, pixels: &[P::Pixel]) -> Result<usize, ImageInputError> {
+        self.write(bytemuck::cast_slice(pixels))
+    }
+
+    /// Pulls the `width` x `height` region at (`x`, `y`) of the [`GpuImage`] into a [`Vec`], row by row.
+    /// An empty region reads an empty [`Vec`].
+    pub async fn read_region(
+        &self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<Vec<u8>, ImageOutputError> {
+        let (origin, size) = region(self.size, x, y, width, height).ok_or_else(|| {
+            ImageOutputError::RegionOutOfBounds {
+                label: self.label.clone(),
+                x,
+                y,
+                width,
+                height,
+            }
+        })?;
+
+        let label = self.label.as_deref().unwrap_or("GpuImage::read_region");
+
+        let mut buf = vec![0u8; byte_count(size, P::byte_size())];
+        download_region(
+            &self.fw,
+            texture_copy(&self.texture, 0, origin),
+            label,
+            P::byte_size() as u32,
+            size,
+            &mut buf,
+        )
+        .await?;
+
+        Ok(buf)
+    }
+
+    /// Blocking version of `GpuImage::read_region()`.
+    pub fn read_region_blocking(
+        &self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<Vec<u8>, ImageOutputError> {
+        self.fw.block_on(self.read_region(x, y, width, height))
+    }
+
+    /// Writes `data` into the `width` x `height` region at (`x`, `y`) of this [`GpuImage`],
+    /// returning how many pixels were written. The operation is instantly offloaded.
+    ///
+    /// `data` must contain exactly `width * height` pixels, row by row.
+    pub fn write_region(
+        &self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        data: &[u8],
+    ) -> Result<usize, ImageInputError> {
+        let (origin, size) = region(self.size, x, y, width, height).ok_or_else(|| {
+            ImageInputError::RegionOutOfBounds {
+                label: self.label.clone(),
+                x,
+                y,
+                width,
+                height,
+            }
+        })?;
+
+        let required = byte_count(size, P::byte_size());
+        if data.len() != required {
+            return Err(ImageInputError::SizeMismatch {
+                label: self.label.clone(),
+                required,
+                current: data.len(),
+            });
+        }
+
+        upload_region(
+            &self.fw,
+            texture_copy(&self.texture, 0, origin),
+            self.label.as_deref().unwrap_or("GpuImage::write_region"),
+            P::byte_size() as u32,
+            size,
+            data,
+        );
+
+        Ok(pixel_count(size))
     }
 
     /// Writes a buffer into this [`GpuImage`], returning how many pixels were written. The operation is instantly offloaded.
@@ -261,21 +1827,28 @@ where
     /// This function will attempt to write the entire contents of `buf`, unless its capacity
     /// exceeds the one of the image, in which case the first `width * height` pixels are written.
     pub fn write(&self, buf: &[u8]) -> Result<usize, ImageInputError> {
-        if buf.len() % P::byte_size() != 0 {
-            return Err(ImageInputError::NotIntegerPixelNumber);
+        if !buf.len().is_multiple_of(P::byte_size()) {
+            return Err(ImageInputError::NotIntegerPixelNumber {
+                label: self.label.clone(),
+            });
         }
-        if buf.len() % (P::byte_size() * self.size.width as usize) != 0 {
-            return Err(ImageInputError::NotIntegerRowNumber);
+        if !buf
+            .len()
+            .is_multiple_of(P::byte_size() * self.size.width as usize)
+        {
+            return Err(ImageInputError::NotIntegerRowNumber {
+                label: self.label.clone(),
+            });
         }
 
-        let image_bytes = P::byte_size() * (self.size.width * self.size.height) as usize;
+        let image_bytes = byte_count(self.size, P::byte_size());
 
         let (write_buf, size) = match buf.len().cmp(&image_bytes) {
             std::cmp::Ordering::Less => (
                 buf,
                 wgpu::Extent3d {
                     width: self.size.width,
-                    height: (buf.len() / P::byte_size()) as u32 / self.size.width,
+                    height: (buf.len() / (P::byte_size() * self.size.width as usize)) as u32,
                     depth_or_array_layers: 1,
                 },
             ),
@@ -283,35 +1856,65 @@ where
             std::cmp::Ordering::Greater => (&buf[..image_bytes], self.size),
         };
 
-        self.fw.queue.write_texture(
-            self.texture.as_image_copy(),
-            write_buf,
-            wgpu::ImageDataLayout {
-                offset: 0,
-                bytes_per_row: Some(P::byte_size() as u32 * self.size.width),
-                rows_per_image: None,
-            },
+        upload_region(
+            &self.fw,
+            texture_copy(&self.texture, 0, wgpu::Origin3d::ZERO),
+            self.label.as_deref().unwrap_or("GpuImage::write"),
+            P::byte_size() as u32,
             size,
+            write_buf,
         );
 
-        let encoder = self
-            .fw
-            .device
-            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                label: Some("GpuImage::write"),
-            });
+        Ok(pixel_count(size))
+    }
+}
 
-        self.fw.queue.submit(Some(encoder.finish()));
+impl<P> sealed::BuildableImage for GpuConstImage<P>
+where
+    P: PixelInfo,
+{
+    fn from_texture(
+        fw: Framework,
+        texture: wgpu::Texture,
+        size: wgpu::Extent3d,
+        label: Option<String>,
+        allocation: memory::Allocation,
+    ) -> Self {
+        let full_view = texture.create_view(&wgpu::TextureViewDescriptor {
+            label: label.as_deref(),
+            ..Default::default()
+        });
+
+        let mip_views = (0..texture.mip_level_count())
+            .map(|level| {
+                texture.create_view(&wgpu::TextureViewDescriptor {
+                    label: label.as_deref(),
+                    base_mip_level: level,
+                    mip_level_count: Some(1),
+                    ..Default::default()
+                })
+            })
+            .collect();
 
-        Ok((size.width * size.height) as usize)
+        Self {
+            fw,
+            texture,
+            size,
+            full_view,
+            mip_views,
+            label,
+            blit_target: OnceLock::new(),
+            _allocation: allocation,
+            pixel: PhantomData,
+        }
     }
 }
 
-impl<'fw, P> ImgOps<'fw> for GpuConstImage<'fw, P>
+impl<P> ImgOps for GpuConstImage<P>
 where
     P: PixelInfo,
 {
-    fn as_binding_resource(&self) -> wgpu::BindingResource {
+    fn as_binding_resource(&self) -> wgpu::BindingResource<'_> {
         wgpu::BindingResource::TextureView(&self.full_view)
     }
 
@@ -327,126 +1930,518 @@ where
         (self.size.width, self.size.height)
     }
 
-    fn new(fw: &'fw crate::Framework, width: u32, height: u32) -> Self {
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    fn new(fw: &Framework, width: u32, height: u32) -> Self {
+        Self::try_new(fw, width, height).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn try_new(fw: &Framework, width: u32, height: u32) -> Result<Self, ImageInputError> {
+        Self::builder(fw, width, height).build()
+    }
+
+    fn from_bytes(fw: &Framework, data: &[u8], width: u32, height: u32) -> Self {
+        Self::try_from_bytes(fw, data, width, height).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn try_from_bytes(
+        fw: &Framework,
+        data: &[u8],
+        width: u32,
+        height: u32,
+    ) -> Result<Self, ImageInputError> {
+        Self::builder(fw, width, height).data(data).build()
+    }
+
+    /// Constructs an image from a [`wgpu::Texture`], its [`wgpu::Extent3d`] and a debug `label`.
+    ///
+    /// # Safety
+    /// If any of the following conditions are not satisfied, the image will
+    /// panic at any time during its usage.
+    /// - `texture` needs to be `wgpu::TextureUsages::TEXTURE_BINDING`, `wgpu::TextureUsages::COPY_SRC`
+    ///   and `wgpu::TextureUsages::COPY_DST` usable.
+    /// - `T` needs to be the exact same codification `texture` is.
+    /// - `dimensions` needs to have the exact `width` and `height` of `texture` and `depth_or_array_layers = 1`
+    fn from_labelled_gpu_parts(
+        fw: &Framework,
+        texture: wgpu::Texture,
+        dimensions: wgpu::Extent3d,
+        label: Option<String>,
+    ) -> Self {
+        let allocation = fw.track(
+            MemoryKind::Image,
+            memory::texture_size(&texture),
+            label.as_deref(),
+        );
+
+        <Self as sealed::BuildableImage>::from_texture(
+            fw.clone(),
+            texture,
+            dimensions,
+            label,
+            allocation,
+        )
+    }
+
+    fn into_gpu_parts(self) -> (wgpu::Texture, wgpu::Extent3d) {
+        (self.texture, self.size)
+    }
+}
+
+impl<P> GpuConstImage<P>
+where
+    P: PixelInfo,
+{
+    /// Creates an [`ImageBuilder`] of a [`GpuConstImage`] with the desired `width` and `height`.
+    pub fn builder<'a>(fw: &Framework, width: u32, height: u32) -> ImageBuilder<'a, P, Self> {
         let size = wgpu::Extent3d {
             width,
             height,
             depth_or_array_layers: 1,
         };
 
+        ImageBuilder::new(
+            fw.clone(),
+            size,
+            wgpu::TextureDimension::D2,
+            GPU_CONST_IMAGE_USAGES,
+        )
+    }
+
+    /// Constructs a new [`GpuConstImage`] of `width` x `height` from `pixels`, laid out row by row.
+    pub fn from_pixels(fw: &Framework, pixels: &[P::Pixel], width: u32, height: u32) -> Self {
+        Self::from_bytes(fw, bytemuck::cast_slice(pixels), width, height)
+    }
+
+    /// Returns the number of mip levels of the image.
+    pub fn mip_level_count(&self) -> u32 {
+        self.texture.mip_level_count()
+    }
+
+    /// Returns the `width` and `height` of the mip `level` of the image.
+    pub fn mip_dimensions(&self, level: u32) -> Option<(u32, u32)> {
+        (level < self.mip_level_count()).then(|| {
+            let size = self.size.mip_level_size(level, wgpu::TextureDimension::D2);
+            (size.width, size.height)
+        })
+    }
+
+    /// Returns the storage image written by [`GpuImage::blit_to`] (and by
+    /// [`GpuConstImage::generate_mipmaps`] on OpenGL) before being copied into this image,
+    /// allocating it on first use.
+    fn blit_target(&self) -> Result<&BlitTarget, ImageCopyError> {
+        if let Some(target) = self.blit_target.get() {
+            return Ok(target);
+        }
+
+        let label = format!(
+            "{} (blit target)",
+            self.label.as_deref().unwrap_or("GpuImage::blit_to")
+        );
         let format = P::wgpu_format();
 
-        let texture = fw.device.create_texture(&wgpu::TextureDescriptor {
-            label: Some("GpuConstImage::new"),
-            size,
-            dimension: wgpu::TextureDimension::D2,
+        let allocation = self.fw.allocate(
+            MemoryKind::Image,
+            memory::image_size(format, self.size, wgpu::TextureDimension::D2, 1),
+            Some(&label),
+        )?;
+        let texture = self.fw.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(&label),
+            size: self.size,
             mip_level_count: 1,
             sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
             format,
-            usage: GPU_CONST_IMAGE_USAGES,
-            view_formats: &[format],
+            usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
         });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
 
-        let full_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-
-        Self {
-            fw,
+        Ok(self.blit_target.get_or_init(|| BlitTarget {
             texture,
-            size,
-            full_view,
-            pixel: PhantomData,
-        }
+            view,
+            _allocation: allocation,
+        }))
     }
 
-    fn from_bytes(fw: &'fw crate::Framework, data: &[u8], width: u32, height: u32) -> Self {
-        let size = wgpu::Extent3d {
-            width,
-            height,
-            depth_or_array_layers: 1,
-        };
+    /// Fills every mip level after the first one by downsampling the previous level
+    /// on the GPU with a 2x2 box filter.
+    ///
+    /// Integer formats are averaged with integer arithmetic, so their sums must fit in
+    /// the shader-side texel type. The kernel is built once per format and reused by every
+    /// [`Framework`] clone.
+    ///
+    /// Fails if the pixel format does not support storage access on this device.
+    pub fn generate_mipmaps(&self) -> Result<(), ImageCopyError> {
+        if self.mip_level_count() == 1 {
+            return Ok(());
+        }
 
-        let format = P::wgpu_format();
+        if !self
+            .texture
+            .usage()
+            .contains(wgpu::TextureUsages::STORAGE_BINDING)
+        {
+            return Err(ImageCopyError::UnsupportedFormat {
+                label: self.label.clone(),
+                format: P::wgpu_format(),
+            });
+        }
 
-        let texture = fw.device.create_texture_with_data(
-            &fw.queue,
-            &wgpu::TextureDescriptor {
-                label: Some("GpuConstImage::from_bytes"),
-                size,
-                dimension: wgpu::TextureDimension::D2,
-                mip_level_count: 1,
-                sample_count: 1,
-                format,
-                usage: GPU_CONST_IMAGE_USAGES,
-                view_formats: &[format],
+        let kernel = ImageKernel::get(
+            &self.fw,
+            ImageKernelKey::Mip {
+                sample: P::wgpu_texture_sample(),
+                format: P::wgpu_format(),
             },
-            wgpu::util::TextureDataOrder::LayerMajor,
-            data,
-        );
+            self.label.as_deref(),
+        )?;
 
-        let full_view = texture.create_view(&Default::default());
+        let label = self
+            .label
+            .as_deref()
+            .unwrap_or("GpuConstImage::generate_mipmaps");
+        let mut encoder = self
+            .fw
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some(label) });
+
+        // wgpu's OpenGL backend emulates views by setting the base and max level of the
+        // whole texture, so a storage view of one level is out of range while the previous
+        // level is sampled. There each level is written into the blit target and copied back.
+        let staging = match self.fw.adapter.get_info().backend {
+            wgpu::Backend::Gl => Some(self.blit_target()?),
+            _ => None,
+        };
 
-        Self {
-            fw,
-            texture,
+        // Each pass reads the view of the previous level and writes the view of the next one.
+        for level in 1..self.mip_level_count() {
+            let size = self.size.mip_level_size(level, wgpu::TextureDimension::D2);
+            let src = &self.mip_views[level as usize - 1];
+
+            match staging {
+                Some(target) => {
+                    kernel.record(&self.fw, &mut encoder, src, &target.view, size, label);
+                    encoder.copy_texture_to_texture(
+                        target.texture.as_image_copy(),
+                        texture_copy(&self.texture, level, wgpu::Origin3d::ZERO),
+                        size,
+                    );
+                }
+                None => {
+                    let dst = &self.mip_views[level as usize];
+                    kernel.record(&self.fw, &mut encoder, src, dst, size, label);
+                }
+            }
+        }
+
+        self.fw.queue.submit(Some(encoder.finish()));
+
+        Ok(())
+    }
+
+    /// Pulls all the pixels of the mip `level` of the image into a [`Vec`], row by row.
+    pub async fn read_mip(&self, level: u32) -> Result<Vec<u8>, ImageOutputError> {
+        if level >= self.mip_level_count() {
+            return Err(ImageOutputError::MipLevelOutOfBounds {
+                label: self.label.clone(),
+                level,
+                levels: self.mip_level_count(),
+            });
+        }
+
+        let size = self.size.mip_level_size(level, wgpu::TextureDimension::D2);
+
+        let mut buf = vec![0u8; byte_count(size, P::byte_size())];
+        download_region(
+            &self.fw,
+            texture_copy(&self.texture, level, wgpu::Origin3d::ZERO),
+            self.label.as_deref().unwrap_or("GpuConstImage::read_mip"),
+            P::byte_size() as u32,
             size,
-            full_view,
-            pixel: PhantomData,
+            &mut buf,
+        )
+        .await?;
+
+        Ok(buf)
+    }
+
+    /// Blocking version of `GpuConstImage::read_mip()`.
+    pub fn read_mip_blocking(&self, level: u32) -> Result<Vec<u8>, ImageOutputError> {
+        self.fw.block_on(self.read_mip(level))
+    }
+
+    /// Writes `data` into the mip `level` of the image, returning how many pixels were written.
+    /// The operation is instantly offloaded.
+    ///
+    /// `data` must contain exactly every pixel of the level, row by row.
+    pub fn write_mip(&self, level: u32, data: &[u8]) -> Result<usize, ImageInputError> {
+        if level >= self.mip_level_count() {
+            return Err(ImageInputError::MipLevelOutOfBounds {
+                label: self.label.clone(),
+                level,
+                levels: self.mip_level_count(),
+            });
+        }
+
+        let size = self.size.mip_level_size(level, wgpu::TextureDimension::D2);
+
+        let pixels = pixel_count(size);
+        if data.len() != byte_count(size, P::byte_size()) {
+            return Err(ImageInputError::SizeMismatch {
+                label: self.label.clone(),
+                required: byte_count(size, P::byte_size()),
+                current: data.len(),
+            });
         }
+
+        upload_region(
+            &self.fw,
+            texture_copy(&self.texture, level, wgpu::Origin3d::ZERO),
+            self.label.as_deref().unwrap_or("GpuConstImage::write_mip"),
+            P::byte_size() as u32,
+            size,
+            data,
+        );
+
+        Ok(pixels)
     }
 
-    /// Constructs an image from a [`wgpu::Texture`] and its [`wgpu::Extent3d`].
+    /// Fills this [`GpuConstImage`] on the GPU with the pixels of `src`, laid out
+    /// row by row and tightly packed, e.g. the output of a previous compute pass.
     ///
-    /// # Safety
-    /// If any of the following conditions are not satisfied, the image will
-    /// panic at any time during its usage.
-    /// - `texture` needs to be `wgpu::TextureUsages::TEXTURE_BINDING` and `wgpu::TextureUsages::COPY_SRC` usable.
-    /// - `T` needs to be the exact same codification `texture` is.
-    /// - `dimensions` needs to have the exact `width` and `height` of `texture` and `depth_or_array_layers = 1`
-    fn from_gpu_parts(
-        fw: &'fw crate::Framework,
-        texture: wgpu::Texture,
-        dimensions: wgpu::Extent3d,
-    ) -> Self {
-        let full_view = texture.create_view(&Default::default());
+    /// `src` must be at least `width * height * P::byte_size()` bytes long. When the rows
+    /// need padding to satisfy `wgpu::COPY_BYTES_PER_ROW_ALIGNMENT`, the pixels are repacked
+    /// into a GPU staging buffer by a compute kernel before being copied into the image.
+    pub fn copy_from_buffer<T>(&self, src: &GpuBuffer<T>) -> Result<(), ImageCopyError>
+    where
+        T: bytemuck::Pod,
+    {
+        let (unpadded_bytes_per_row, padded_bytes_per_row) = flat_copy_layout(
+            self.label.as_deref(),
+            src.label(),
+            src.size(),
+            P::byte_size() as u32,
+            self.size,
+        )?;
+
+        let label = self
+            .label
+            .as_deref()
+            .unwrap_or("GpuConstImage::copy_from_buffer");
 
-        Self {
-            fw,
-            texture,
-            size: dimensions,
-            full_view,
-            pixel: PhantomData,
+        let mut encoder = self
+            .fw
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some(label) });
+
+        if unpadded_bytes_per_row == padded_bytes_per_row {
+            encoder.copy_buffer_to_texture(
+                wgpu::ImageCopyBuffer {
+                    buffer: src.as_gpu_buffer(),
+                    layout: wgpu::ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: Some(padded_bytes_per_row),
+                        rows_per_image: Some(self.size.height),
+                    },
+                },
+                self.texture.as_image_copy(),
+                self.size,
+            );
+        } else {
+            let staging =
+                FlatStaging::new(&self.fw, label, P::byte_size() as u32, self.size, true)?;
+
+            // Buffer copies move whole words: the last bytes are copied into the image afterwards.
+            let required = unpadded_bytes_per_row as u64 * self.size.height as u64;
+            let aligned = required / wgpu::COPY_BUFFER_ALIGNMENT * wgpu::COPY_BUFFER_ALIGNMENT;
+            if aligned > 0 {
+                encoder.copy_buffer_to_buffer(src.as_gpu_buffer(), 0, &staging.packed, 0, aligned);
+            }
+
+            record_repack(
+                &self.fw,
+                &mut encoder,
+                &staging.packed,
+                unpadded_bytes_per_row,
+                &staging.padded,
+                padded_bytes_per_row,
+                unpadded_bytes_per_row,
+                self.size.height,
+                label,
+            )?;
+
+            encoder.copy_buffer_to_texture(
+                wgpu::ImageCopyBuffer {
+                    buffer: &staging.padded,
+                    layout: wgpu::ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: Some(padded_bytes_per_row),
+                        rows_per_image: Some(self.size.height),
+                    },
+                },
+                self.texture.as_image_copy(),
+                self.size,
+            );
+
+            for (offset, origin, width) in
+                pixel_runs(aligned, required, P::byte_size() as u32, self.size.width)
+            {
+                encoder.copy_buffer_to_texture(
+                    wgpu::ImageCopyBuffer {
+                        buffer: src.as_gpu_buffer(),
+                        layout: wgpu::ImageDataLayout {
+                            offset,
+                            bytes_per_row: None,
+                            rows_per_image: None,
+                        },
+                    },
+                    texture_copy(&self.texture, 0, origin),
+                    wgpu::Extent3d {
+                        width,
+                        height: 1,
+                        depth_or_array_layers: 1,
+                    },
+                );
+            }
         }
+
+        self.fw.queue.submit(Some(encoder.finish()));
+
+        Ok(())
     }
 
-    fn into_gpu_parts(self) -> (wgpu::Texture, wgpu::Extent3d) {
-        (self.texture, self.size)
+    /// Writes pixels into this [`GpuConstImage`], returning how many pixels were written.
+    /// The operation is instantly offloaded.
+    ///
+    /// Typed version of [`GpuConstImage::write`].
+    pub fn write_pixels(&self, pixels: &[P::Pixel]) -> Result<usize, ImageInputError> {
+        self.write(bytemuck::cast_slice(pixels))
+    }
+
+    /// Pulls the `width` x `height` region at (`x`, `y`) of the [`GpuConstImage`] into a [`Vec`], row by row.
+    /// An empty region reads an empty [`Vec`].
+    pub async fn read_region(
+        &self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<Vec<u8>, ImageOutputError> {
+        let (origin, size) = region(self.size, x, y, width, height).ok_or_else(|| {
+            ImageOutputError::RegionOutOfBounds {
+                label: self.label.clone(),
+                x,
+                y,
+                width,
+                height,
+            }
+        })?;
+
+        let label = self
+            .label
+            .as_deref()
+            .unwrap_or("GpuConstImage::read_region");
+
+        let mut buf = vec![0u8; byte_count(size, P::byte_size())];
+        download_region(
+            &self.fw,
+            texture_copy(&self.texture, 0, origin),
+            label,
+            P::byte_size() as u32,
+            size,
+            &mut buf,
+        )
+        .await?;
+
+        Ok(buf)
+    }
+
+    /// Blocking version of `GpuConstImage::read_region()`.
+    pub fn read_region_blocking(
+        &self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<Vec<u8>, ImageOutputError> {
+        self.fw.block_on(self.read_region(x, y, width, height))
+    }
+
+    /// Writes `data` into the `width` x `height` region at (`x`, `y`) of this [`GpuConstImage`],
+    /// returning how many pixels were written. The operation is instantly offloaded.
+    ///
+    /// `data` must contain exactly `width * height` pixels, row by row.
+    pub fn write_region(
+        &self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        data: &[u8],
+    ) -> Result<usize, ImageInputError> {
+        let (origin, size) = region(self.size, x, y, width, height).ok_or_else(|| {
+            ImageInputError::RegionOutOfBounds {
+                label: self.label.clone(),
+                x,
+                y,
+                width,
+                height,
+            }
+        })?;
+
+        let required = byte_count(size, P::byte_size());
+        if data.len() != required {
+            return Err(ImageInputError::SizeMismatch {
+                label: self.label.clone(),
+                required,
+                current: data.len(),
+            });
+        }
+
+        upload_region(
+            &self.fw,
+            texture_copy(&self.texture, 0, origin),
+            self.label
+                .as_deref()
+                .unwrap_or("GpuConstImage::write_region"),
+            P::byte_size() as u32,
+            size,
+            data,
+        );
+
+        Ok(pixel_count(size))
     }
-}
 
-impl<'fw, P> GpuConstImage<'fw, P>
-where
-    P: PixelInfo,
-{
     /// Writes a buffer into this [`GpuConstImage`], returning how many pixels were written. The operation is instantly offloaded.
     ///
     /// This function will attempt to write the entire contents of `buf`, unless its capacity
     /// exceeds the one of the image, in which case the first `width * height` pixels are written.
     pub fn write(&self, buf: &[u8]) -> Result<usize, ImageInputError> {
-        if buf.len() % P::byte_size() != 0 {
-            return Err(ImageInputError::NotIntegerPixelNumber);
+        if !buf.len().is_multiple_of(P::byte_size()) {
+            return Err(ImageInputError::NotIntegerPixelNumber {
+                label: self.label.clone(),
+            });
         }
-        if buf.len() % (P::byte_size() * self.size.width as usize) != 0 {
-            return Err(ImageInputError::NotIntegerRowNumber);
+        if !buf
+            .len()
+            .is_multiple_of(P::byte_size() * self.size.width as usize)
+        {
+            return Err(ImageInputError::NotIntegerRowNumber {
+                label: self.label.clone(),
+            });
         }
 
-        let image_bytes = P::byte_size() * (self.size.width * self.size.height) as usize;
+        let image_bytes = byte_count(self.size, P::byte_size());
 
         let (write_buf, size) = match buf.len().cmp(&image_bytes) {
             std::cmp::Ordering::Less => (
                 buf,
                 wgpu::Extent3d {
                     width: self.size.width,
-                    height: buf.len() as u32 / self.size.width,
+                    height: (buf.len() / (P::byte_size() * self.size.width as usize)) as u32,
                     depth_or_array_layers: 1,
                 },
             ),
@@ -454,26 +2449,37 @@ where
             std::cmp::Ordering::Greater => (&buf[..image_bytes], self.size),
         };
 
-        self.fw.queue.write_texture(
-            self.texture.as_image_copy(),
-            write_buf,
-            wgpu::ImageDataLayout {
-                offset: 0,
-                bytes_per_row: Some(P::byte_size() as u32 * self.size.width),
-                rows_per_image: None,
-            },
+        upload_region(
+            &self.fw,
+            texture_copy(&self.texture, 0, wgpu::Origin3d::ZERO),
+            self.label.as_deref().unwrap_or("GpuConstImage::write"),
+            P::byte_size() as u32,
             size,
+            write_buf,
         );
 
-        let encoder = self
-            .fw
-            .device
-            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                label: Some("GpuConstImage::write"),
-            });
+        Ok(pixel_count(size))
+    }
+}
 
-        self.fw.queue.submit(Some(encoder.finish()));
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn runs(start: u64, end: u64, bytes_per_pixel: u32, width: u32) -> Vec<(u64, u32, u32, u32)> {
+        pixel_runs(start, end, bytes_per_pixel, width)
+            .map(|(offset, origin, count)| (offset, origin.x, origin.y, count))
+            .collect()
+    }
 
-        Ok((size.width * size.height) as usize)
+    #[test]
+    fn pixel_runs_split_at_rows() {
+        // Last 3 bytes of a 1 pixel wide R8 image of 7 rows.
+        assert_eq!(runs(4, 7, 1, 1), [(4, 0, 4, 1), (5, 0, 5, 1), (6, 0, 6, 1)]);
+        // Last byte of a 5x5 R8 image.
+        assert_eq!(runs(24, 25, 1, 5), [(24, 4, 4, 1)]);
+        // Last pixel of a 3x3 Rg8 image.
+        assert_eq!(runs(16, 18, 2, 3), [(16, 2, 2, 1)]);
+        assert!(runs(16, 16, 4, 3).is_empty());
     }
 }
@@ -0,0 +1,220 @@
+use std::marker::PhantomData;
+
+use crate::{memory, Framework, GpuConstImage3d, GpuConstImageArray, GpuImage3d, GpuImageArray};
+
+use super::{
+    images::{
+        byte_count, download_region, pixel_count, sealed::BuildableImage, texture_copy,
+        upload_region, ImageBuilder, ImageInputError, ImageOutputError, GPU_CONST_IMAGE_USAGES,
+        GPU_IMAGE_USAGES,
+    },
+    PixelInfo,
+};
+
+macro_rules! layered_image_impl {
+    ($($name:ident, $usages:expr, $dimension:expr, $view_dimension:expr, $layers:literal);+) => {
+        $(
+            impl<P> BuildableImage for $name<P>
+            where
+                P: PixelInfo,
+            {
+                fn from_texture(
+                    fw: Framework,
+                    texture: wgpu::Texture,
+                    size: wgpu::Extent3d,
+                    label: Option<String>,
+                    allocation: memory::Allocation,
+                ) -> Self {
+                    let full_view = texture.create_view(&wgpu::TextureViewDescriptor {
+                        label: label.as_deref(),
+                        dimension: Some($view_dimension),
+                        ..Default::default()
+                    });
+
+                    Self {
+                        fw,
+                        texture,
+                        size,
+                        full_view,
+                        label,
+                        _allocation: allocation,
+                        pixel: PhantomData,
+                    }
+                }
+            }
+
+            impl<P> $name<P>
+            where
+                P: PixelInfo,
+            {
+                #[doc = concat!("Creates an [`ImageBuilder`] of a [`", stringify!($name), "`] with the desired `width`, `height` and number of ", $layers, ".")]
+                pub fn builder<'a>(
+                    fw: &Framework,
+                    width: u32,
+                    height: u32,
+                    layers: u32,
+                ) -> ImageBuilder<'a, P, Self> {
+                    let size = wgpu::Extent3d {
+                        width,
+                        height,
+                        depth_or_array_layers: layers,
+                    };
+
+                    ImageBuilder::new(fw.clone(), size, $dimension, $usages)
+                }
+
+                #[doc = concat!("Returns the `width`, `height` and number of ", $layers, " of the image.")]
+                pub fn dimensions(&self) -> (u32, u32, u32) {
+                    (self.size.width, self.size.height, self.size.depth_or_array_layers)
+                }
+
+                /// Returns the debug label of the image, if any.
+                pub fn label(&self) -> Option<&str> {
+                    self.label.as_deref()
+                }
+
+                /// Gets the inner [`wgpu::Texture`] of the image.
+                pub fn as_gpu_texture(&self) -> &wgpu::Texture {
+                    &self.texture
+                }
+
+                /// Returns a [`wgpu::BindingResource`] of the whole image.
+                pub fn as_binding_resource(&self) -> wgpu::BindingResource<'_> {
+                    wgpu::BindingResource::TextureView(&self.full_view)
+                }
+
+                /// Pulls all the pixels of the image into a [`Vec`], layer by layer and row by row.
+                pub async fn read_vec(&self) -> Result<Vec<u8>, ImageOutputError> {
+                    let mut buf = vec![0u8; byte_count(self.size, P::byte_size())];
+
+                    download_region(
+                        &self.fw,
+                        texture_copy(&self.texture, 0, wgpu::Origin3d::ZERO),
+                        self.label.as_deref().unwrap_or(concat!(stringify!($name), "::read_vec")),
+                        P::byte_size() as u32,
+                        self.size,
+                        &mut buf,
+                    )
+                    .await?;
+
+                    Ok(buf)
+                }
+
+                #[doc = concat!("Blocking version of `", stringify!($name), "::read_vec()`.")]
+                pub fn read_vec_blocking(&self) -> Result<Vec<u8>, ImageOutputError> {
+                    self.fw.block_on(self.read_vec())
+                }
+
+                #[doc = concat!("Pulls the pixels of one of the ", $layers, " of the image into a [`Vec`], row by row.")]
+                pub async fn read_layer(&self, layer: u32) -> Result<Vec<u8>, ImageOutputError> {
+                    let layers = self.size.depth_or_array_layers;
+                    if layer >= layers {
+                        return Err(ImageOutputError::LayerOutOfBounds {
+                            label: self.label.clone(),
+                            layer,
+                            layers,
+                        });
+                    }
+
+                    let layer_size = wgpu::Extent3d {
+                        depth_or_array_layers: 1,
+                        ..self.size
+                    };
+                    let mut buf = vec![0u8; byte_count(layer_size, P::byte_size())];
+
+                    download_region(
+                        &self.fw,
+                        texture_copy(&self.texture, 0, wgpu::Origin3d { x: 0, y: 0, z: layer }),
+                        self.label.as_deref().unwrap_or(concat!(stringify!($name), "::read_layer")),
+                        P::byte_size() as u32,
+                        layer_size,
+                        &mut buf,
+                    )
+                    .await?;
+
+                    Ok(buf)
+                }
+
+                #[doc = concat!("Blocking version of `", stringify!($name), "::read_layer()`.")]
+                pub fn read_layer_blocking(&self, layer: u32) -> Result<Vec<u8>, ImageOutputError> {
+                    self.fw.block_on(self.read_layer(layer))
+                }
+
+                /// Writes `data` into the whole image, returning how many pixels were written.
+                /// The operation is instantly offloaded.
+                ///
+                #[doc = concat!("`data` must contain every pixel of the image, ordered by ", $layers, " and then by rows.")]
+                pub fn write(&self, data: &[u8]) -> Result<usize, ImageInputError> {
+                    let pixels = pixel_count(self.size);
+
+                    let required = byte_count(self.size, P::byte_size());
+                    if data.len() != required {
+                        return Err(ImageInputError::SizeMismatch {
+                            label: self.label.clone(),
+                            required,
+                            current: data.len(),
+                        });
+                    }
+
+                    upload_region(
+                        &self.fw,
+                        texture_copy(&self.texture, 0, wgpu::Origin3d::ZERO),
+                        self.label.as_deref().unwrap_or(concat!(stringify!($name), "::write")),
+                        P::byte_size() as u32,
+                        self.size,
+                        data,
+                    );
+
+                    Ok(pixels)
+                }
+
+                #[doc = concat!("Writes `data` into one of the ", $layers, " of the image, returning how many pixels were written.")]
+                /// The operation is instantly offloaded.
+                ///
+                /// `data` must contain exactly `width * height` pixels, row by row.
+                pub fn write_layer(&self, layer: u32, data: &[u8]) -> Result<usize, ImageInputError> {
+                    let layers = self.size.depth_or_array_layers;
+                    if layer >= layers {
+                        return Err(ImageInputError::LayerOutOfBounds {
+                            label: self.label.clone(),
+                            layer,
+                            layers,
+                        });
+                    }
+
+                    let layer_size = wgpu::Extent3d {
+                        depth_or_array_layers: 1,
+                        ..self.size
+                    };
+                    let pixels = pixel_count(layer_size);
+                    let required = byte_count(layer_size, P::byte_size());
+                    if data.len() != required {
+                        return Err(ImageInputError::SizeMismatch {
+                            label: self.label.clone(),
+                            required,
+                            current: data.len(),
+                        });
+                    }
+
+                    upload_region(
+                        &self.fw,
+                        texture_copy(&self.texture, 0, wgpu::Origin3d { x: 0, y: 0, z: layer }),
+                        self.label.as_deref().unwrap_or(concat!(stringify!($name), "::write_layer")),
+                        P::byte_size() as u32,
+                        layer_size,
+                        data,
+                    );
+
+                    Ok(pixels)
+                }
+            }
+        )+
+    };
+}
+
+layered_image_impl! {
+    GpuImage3d, GPU_IMAGE_USAGES, wgpu::TextureDimension::D3, wgpu::TextureViewDimension::D3, "depth slices";
+    GpuConstImage3d, GPU_CONST_IMAGE_USAGES, wgpu::TextureDimension::D3, wgpu::TextureViewDimension::D3, "depth slices";
+    GpuImageArray, GPU_IMAGE_USAGES, wgpu::TextureDimension::D2, wgpu::TextureViewDimension::D2Array, "layers";
+    GpuConstImageArray, GPU_CONST_IMAGE_USAGES, wgpu::TextureDimension::D2, wgpu::TextureViewDimension::D2Array, "layers"
+}
@@ -1,4 +1,275 @@
-use crate::Sampler;
+use thiserror::Error;
+
+use crate::{Framework, Sampler, SamplerFilterMode, SamplerWrapMode};
+
+#[derive(Error, Debug)]
+pub enum SamplerError {
+    #[error(
+        "Sampler {} requires the missing device features {features:?}.",
+        .label.as_deref().unwrap_or("<unlabelled>")
+    )]
+    MissingFeatures {
+        label: Option<String>,
+        features: wgpu::Features,
+    },
+    #[error(
+        "Anisotropy clamp {clamp} of sampler {} is not in the range 1..=16.",
+        .label.as_deref().unwrap_or("<unlabelled>")
+    )]
+    InvalidAnisotropy { label: Option<String>, clamp: u16 },
+    #[error(
+        "Sampler {} uses anisotropic filtering, which requires linear min, mag and mipmap filters.",
+        .label.as_deref().unwrap_or("<unlabelled>")
+    )]
+    AnisotropyRequiresLinear { label: Option<String> },
+    #[error(
+        "Invalid LOD clamp of sampler {} (min {min}, max {max}).",
+        .label.as_deref().unwrap_or("<unlabelled>")
+    )]
+    InvalidLodClamp {
+        label: Option<String>,
+        min: f32,
+        max: f32,
+    },
+}
+
+impl SamplerWrapMode {
+    fn wgpu_address_mode(self) -> wgpu::AddressMode {
+        match self {
+            SamplerWrapMode::ClampToEdge => wgpu::AddressMode::ClampToEdge,
+            SamplerWrapMode::Repeat => wgpu::AddressMode::Repeat,
+            SamplerWrapMode::MirrorRepeat => wgpu::AddressMode::MirrorRepeat,
+            SamplerWrapMode::ClampToBorder => wgpu::AddressMode::ClampToBorder,
+        }
+    }
+}
+
+impl SamplerFilterMode {
+    fn wgpu_filter_mode(self) -> wgpu::FilterMode {
+        match self {
+            SamplerFilterMode::Nearest => wgpu::FilterMode::Nearest,
+            SamplerFilterMode::Linear => wgpu::FilterMode::Linear,
+        }
+    }
+}
+
+/// Builder of [`Sampler`]s with per-axis wrap modes, separate filters, LOD clamps,
+/// anisotropy, border colour and comparison function.
+///
+/// Created by [`Sampler::builder`].
+///
+/// # Example
+/// ```no_run
+/// # use gpgpu::*;
+/// # let fw = Framework::default();
+/// let sampler = Sampler::builder(&fw)
+///     .label("Pyramid sampler")
+///     .address_mode_u(SamplerWrapMode::Repeat)
+///     .filter(SamplerFilterMode::Linear)
+///     .lod_clamp(0.0, 4.0)
+///     .anisotropy_clamp(8)
+///     .build()
+///     .unwrap();
+/// ```
+pub struct SamplerBuilder {
+    fw: Framework,
+    label: Option<String>,
+    address_modes: [SamplerWrapMode; 3],
+    mag_filter: SamplerFilterMode,
+    min_filter: SamplerFilterMode,
+    mipmap_filter: SamplerFilterMode,
+    lod_min_clamp: f32,
+    lod_max_clamp: f32,
+    anisotropy_clamp: u16,
+    border_color: Option<wgpu::SamplerBorderColor>,
+    compare: Option<wgpu::CompareFunction>,
+}
+
+impl SamplerBuilder {
+    fn new(fw: &Framework) -> Self {
+        Self {
+            fw: fw.clone(),
+            label: None,
+            address_modes: [SamplerWrapMode::ClampToEdge; 3],
+            mag_filter: SamplerFilterMode::Nearest,
+            min_filter: SamplerFilterMode::Nearest,
+            mipmap_filter: SamplerFilterMode::Nearest,
+            lod_min_clamp: 0.0,
+            lod_max_clamp: f32::MAX,
+            anisotropy_clamp: 1,
+            border_color: None,
+            compare: None,
+        }
+    }
+
+    /// Sets the debug label of the sampler.
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Sets the wrap mode of the u, v and w axes. Defaults to [`SamplerWrapMode::ClampToEdge`].
+    pub fn address_mode(mut self, mode: SamplerWrapMode) -> Self {
+        self.address_modes = [mode; 3];
+        self
+    }
+
+    /// Sets the wrap mode of the u (x) axis.
+    pub fn address_mode_u(mut self, mode: SamplerWrapMode) -> Self {
+        self.address_modes[0] = mode;
+        self
+    }
+
+    /// Sets the wrap mode of the v (y) axis.
+    pub fn address_mode_v(mut self, mode: SamplerWrapMode) -> Self {
+        self.address_modes[1] = mode;
+        self
+    }
+
+    /// Sets the wrap mode of the w (z) axis.
+    pub fn address_mode_w(mut self, mode: SamplerWrapMode) -> Self {
+        self.address_modes[2] = mode;
+        self
+    }
+
+    /// Sets the magnification, minification and mipmap filters. Defaults to [`SamplerFilterMode::Nearest`].
+    pub fn filter(mut self, mode: SamplerFilterMode) -> Self {
+        self.mag_filter = mode;
+        self.min_filter = mode;
+        self.mipmap_filter = mode;
+        self
+    }
+
+    /// Sets the filter used when the texture is magnified.
+    pub fn mag_filter(mut self, mode: SamplerFilterMode) -> Self {
+        self.mag_filter = mode;
+        self
+    }
+
+    /// Sets the filter used when the texture is minified.
+    pub fn min_filter(mut self, mode: SamplerFilterMode) -> Self {
+        self.min_filter = mode;
+        self
+    }
+
+    /// Sets the filter used between mip levels.
+    pub fn mipmap_filter(mut self, mode: SamplerFilterMode) -> Self {
+        self.mipmap_filter = mode;
+        self
+    }
+
+    /// Sets the minimum and maximum level of detail used when sampling mip levels.
+    pub fn lod_clamp(mut self, min: f32, max: f32) -> Self {
+        self.lod_min_clamp = min;
+        self.lod_max_clamp = max;
+        self
+    }
+
+    /// Sets the maximum anisotropy, in the range `1..=16`. Defaults to `1` (no anisotropic filtering).
+    ///
+    /// Values above `1` require all the filters to be [`SamplerFilterMode::Linear`].
+    pub fn anisotropy_clamp(mut self, clamp: u16) -> Self {
+        self.anisotropy_clamp = clamp;
+        self
+    }
+
+    /// Sets the colour used by [`SamplerWrapMode::ClampToBorder`] axes.
+    /// Defaults to [`wgpu::SamplerBorderColor::TransparentBlack`] when any axis clamps to the border.
+    ///
+    /// [`wgpu::SamplerBorderColor::Zero`] requires `wgpu::Features::ADDRESS_MODE_CLAMP_TO_ZERO`.
+    pub fn border_color(mut self, color: wgpu::SamplerBorderColor) -> Self {
+        self.border_color = Some(color);
+        self
+    }
+
+    /// Turns the sampler into a comparison sampler using `compare` (e.g. for depth textures).
+    pub fn compare(mut self, compare: wgpu::CompareFunction) -> Self {
+        self.compare = Some(compare);
+        self
+    }
+
+    /// Creates the [`Sampler`].
+    ///
+    /// Fails if the configuration is invalid or requires features the device doesn't have,
+    /// like `wgpu::Features::ADDRESS_MODE_CLAMP_TO_BORDER` for [`SamplerWrapMode::ClampToBorder`].
+    pub fn build(self) -> Result<Sampler, SamplerError> {
+        let clamps_to_border = self.address_modes.contains(&SamplerWrapMode::ClampToBorder);
+
+        let mut required = wgpu::Features::empty();
+        if clamps_to_border {
+            required |= wgpu::Features::ADDRESS_MODE_CLAMP_TO_BORDER;
+        }
+        if clamps_to_border && self.border_color == Some(wgpu::SamplerBorderColor::Zero) {
+            required |= wgpu::Features::ADDRESS_MODE_CLAMP_TO_ZERO;
+        }
+
+        let missing = required - self.fw.features();
+        if !missing.is_empty() {
+            return Err(SamplerError::MissingFeatures {
+                label: self.label,
+                features: missing,
+            });
+        }
+
+        if !(1..=16).contains(&self.anisotropy_clamp) {
+            return Err(SamplerError::InvalidAnisotropy {
+                label: self.label,
+                clamp: self.anisotropy_clamp,
+            });
+        }
+
+        let filters = [self.mag_filter, self.min_filter, self.mipmap_filter];
+        if self.anisotropy_clamp > 1 && filters.contains(&SamplerFilterMode::Nearest) {
+            return Err(SamplerError::AnisotropyRequiresLinear { label: self.label });
+        }
+
+        if !(0.0..=self.lod_max_clamp).contains(&self.lod_min_clamp) {
+            return Err(SamplerError::InvalidLodClamp {
+                label: self.label,
+                min: self.lod_min_clamp,
+                max: self.lod_max_clamp,
+            });
+        }
+
+        let border_color = if clamps_to_border {
+            Some(
+                self.border_color
+                    .unwrap_or(wgpu::SamplerBorderColor::TransparentBlack),
+            )
+        } else {
+            None
+        };
+
+        let sampler = self.fw.device.create_sampler(&wgpu::SamplerDescriptor {
+            label: self.label.as_deref(),
+            address_mode_u: self.address_modes[0].wgpu_address_mode(),
+            address_mode_v: self.address_modes[1].wgpu_address_mode(),
+            address_mode_w: self.address_modes[2].wgpu_address_mode(),
+            mag_filter: self.mag_filter.wgpu_filter_mode(),
+            min_filter: self.min_filter.wgpu_filter_mode(),
+            mipmap_filter: self.mipmap_filter.wgpu_filter_mode(),
+            lod_min_clamp: self.lod_min_clamp,
+            lod_max_clamp: self.lod_max_clamp,
+            compare: self.compare,
+            anisotropy_clamp: self.anisotropy_clamp,
+            border_color,
+        });
+
+        let binding_type = if self.compare.is_some() {
+            wgpu::SamplerBindingType::Comparison
+        } else if filters.contains(&SamplerFilterMode::Linear) {
+            wgpu::SamplerBindingType::Filtering
+        } else {
+            wgpu::SamplerBindingType::NonFiltering
+        };
+
+        Ok(Sampler {
+            sampler,
+            binding_type,
+            label: self.label,
+        })
+    }
+}
 
 impl Sampler {
     /// Creates a new [`Sampler`] using the given wrap and filter mode.
@@ -7,33 +278,20 @@ impl Sampler {
         wrap_mode: crate::SamplerWrapMode,
         filter_mode: crate::SamplerFilterMode,
     ) -> Self {
-        let address_mode = match wrap_mode {
-            crate::SamplerWrapMode::ClampToEdge => wgpu::AddressMode::ClampToEdge,
-            crate::SamplerWrapMode::Repeat => wgpu::AddressMode::Repeat,
-            crate::SamplerWrapMode::MirrorRepeat => wgpu::AddressMode::MirrorRepeat,
-            crate::SamplerWrapMode::ClampToBorder => wgpu::AddressMode::ClampToBorder,
-        };
-        let wgpu_filter_mode = match filter_mode {
-            crate::SamplerFilterMode::Nearest => wgpu::FilterMode::Nearest,
-            crate::SamplerFilterMode::Linear => wgpu::FilterMode::Linear,
-        };
-        let sampler = fw.device.create_sampler(&wgpu::SamplerDescriptor {
-            label: None,
-            address_mode_u: address_mode,
-            address_mode_v: address_mode,
-            address_mode_w: address_mode,
-            mag_filter: wgpu_filter_mode,
-            min_filter: wgpu_filter_mode,
-            mipmap_filter: wgpu_filter_mode,
-            lod_min_clamp: 0.0,
-            lod_max_clamp: std::f32::MAX,
-            compare: None,
-            anisotropy_clamp: 1,
-            border_color: None,
-        });
-        Self {
-            sampler,
-            filter_mode,
-        }
+        Self::builder(fw)
+            .address_mode(wrap_mode)
+            .filter(filter_mode)
+            .build()
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Creates a [`SamplerBuilder`] to configure every sampler parameter.
+    pub fn builder(fw: &Framework) -> SamplerBuilder {
+        SamplerBuilder::new(fw)
+    }
+
+    /// Returns the debug label of the sampler, if any.
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
     }
 }
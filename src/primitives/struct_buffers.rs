@@ -0,0 +1,370 @@
+use std::marker::PhantomData;
+
+use crate::{memory::MemoryKind, Framework, GpuStructBuffer};
+
+use super::buffers::{self, check_buffer_size, BufferError, BufferResult};
+
+/// Builder of [`GpuStructBuffer`] with custom label, initial data and array alignment.
+///
+/// Created by [`GpuStructBuffer::builder`].
+pub struct StructBufferBuilder<'a, H, T> {
+    fw: Framework,
+    label: Option<String>,
+    header: Option<&'a H>,
+    data: Option<&'a [T]>,
+    capacity: Option<u64>,
+    array_alignment: u64,
+}
+
+impl<'a, H, T> StructBufferBuilder<'a, H, T>
+where
+    H: bytemuck::Pod,
+    T: bytemuck::Pod,
+{
+    /// Sets the debug label of the buffer.
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Sets the initial header. Defaults to a zeroed header.
+    pub fn header(mut self, header: &'a H) -> Self {
+        self.header = Some(header);
+        self
+    }
+
+    /// Sets the initial elements of the array.
+    pub fn data(mut self, data: &'a [T]) -> Self {
+        self.data = Some(data);
+        self
+    }
+
+    /// Sets the number of elements the array can hold.
+    ///
+    /// Defaults to the length of the initial data, if any, or to 0.
+    pub fn capacity(mut self, capacity: u64) -> Self {
+        self.capacity = Some(capacity);
+        self
+    }
+
+    /// Sets the alignment (in bytes) of the array start.
+    ///
+    /// Must match the WGSL alignment of the array element type, which may be bigger than
+    /// its Rust alignment (e.g. 16 for a `vec4<f32>` element represented as `[f32; 4]`).
+    /// Defaults to the Rust alignment of `T`, with a minimum of 4.
+    ///
+    /// Must be a power of two of at least 4 bytes, otherwise [`StructBufferBuilder::build`] fails.
+    pub fn array_alignment(mut self, alignment: u64) -> Self {
+        self.array_alignment = alignment;
+        self
+    }
+
+    /// Creates the buffer.
+    pub fn build(self) -> BufferResult<GpuStructBuffer<H, T>> {
+        // Keeps the array start aligned to `wgpu::COPY_BUFFER_ALIGNMENT`, so writes can be padded.
+        if !self.array_alignment.is_power_of_two() || self.array_alignment < 4 {
+            return Err(BufferError::InvalidAlignment(self.array_alignment));
+        }
+
+        let data_len = self.data.map_or(0, |data| data.len() as u64);
+        let capacity = self.capacity.unwrap_or(data_len);
+
+        if data_len > capacity {
+            return Err(BufferError::DataTooLarge {
+                capacity,
+                len: data_len,
+            });
+        }
+
+        let array_offset =
+            wgpu::util::align_to(std::mem::size_of::<H>() as u64, self.array_alignment);
+        let size =
+            array_offset.saturating_add(capacity.saturating_mul(std::mem::size_of::<T>() as u64));
+        check_buffer_size(&self.fw, size, self.label.as_deref())?;
+
+        let alloc_size = wgpu::util::align_to(size, wgpu::COPY_BUFFER_ALIGNMENT);
+        let allocation = self
+            .fw
+            .allocate(MemoryKind::Buffer, alloc_size, self.label.as_deref())?;
+
+        let buf = self.fw.device.create_buffer(&wgpu::BufferDescriptor {
+            label: self.label.as_deref(),
+            size: alloc_size,
+            usage: wgpu::BufferUsages::STORAGE
+                | wgpu::BufferUsages::COPY_SRC
+                | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: true,
+        });
+
+        {
+            let mut mapped = buf.slice(..).get_mapped_range_mut();
+
+            if let Some(header) = self.header {
+                mapped[..std::mem::size_of::<H>()].copy_from_slice(bytemuck::bytes_of(header));
+            }
+
+            if let Some(data) = self.data {
+                let bytes: &[u8] = bytemuck::cast_slice(data);
+                let start = array_offset as usize;
+                mapped[start..start + bytes.len()].copy_from_slice(bytes);
+            }
+        }
+        buf.unmap();
+
+        Ok(GpuStructBuffer {
+            fw: self.fw,
+            buf,
+            capacity,
+            array_offset,
+            label: self.label,
+            _allocation: allocation,
+            marker: PhantomData,
+        })
+    }
+}
+
+impl<H, T> GpuStructBuffer<H, T>
+where
+    H: bytemuck::Pod,
+    T: bytemuck::Pod,
+{
+    /// Creates a [`StructBufferBuilder`] of a [`GpuStructBuffer`].
+    pub fn builder<'a>(fw: &Framework) -> StructBufferBuilder<'a, H, T> {
+        StructBufferBuilder {
+            fw: fw.clone(),
+            label: None,
+            header: None,
+            data: None,
+            capacity: None,
+            array_alignment: std::mem::align_of::<T>().max(4) as u64,
+        }
+    }
+
+    /// Constructs a new zeroed buffer whose array can hold exactly `capacity` elements.
+    ///
+    /// # Panics
+    /// If the buffer exceeds the limits of the device or the memory budget.
+    /// Use [`GpuStructBuffer::builder`] to handle these errors.
+    pub fn with_capacity(fw: &Framework, capacity: u64) -> Self {
+        Self::builder(fw)
+            .label("GpuStructBuffer::with_capacity")
+            .capacity(capacity)
+            .build()
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Constructs a new buffer from a `header` and the `elements` of its array.
+    ///
+    /// The array capacity will be the `elements` length.
+    ///
+    /// # Panics
+    /// If the buffer exceeds the limits of the device or the memory budget.
+    /// Use [`GpuStructBuffer::builder`] to handle these errors.
+    pub fn from_parts(fw: &Framework, header: &H, elements: &[T]) -> Self {
+        Self::builder(fw)
+            .label("GpuStructBuffer::from_parts")
+            .header(header)
+            .data(elements)
+            .build()
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Returns the number of elements the array can hold.
+    pub fn capacity(&self) -> u64 {
+        self.capacity
+    }
+
+    /// Returns the number of bytes of the buffer, header and padding included.
+    pub fn size(&self) -> u64 {
+        self.array_offset + self.capacity * std::mem::size_of::<T>() as u64
+    }
+
+    /// Returns the byte offset of the array start.
+    pub fn array_offset(&self) -> u64 {
+        self.array_offset
+    }
+
+    /// Returns the debug label of the buffer, if any.
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    /// Returns a [`wgpu::BindingResource`] of the whole buffer.
+    pub fn as_binding_resource(&self) -> wgpu::BindingResource<'_> {
+        self.buf.as_entire_binding()
+    }
+
+    /// Returns the [`wgpu::Buffer`] that handles the GPU data of the buffer.
+    pub fn as_gpu_buffer(&self) -> &wgpu::Buffer {
+        &self.buf
+    }
+
+    /// Checks that the header and array match the layout of the storage buffer
+    /// bound at `group` and `binding` in `shader`.
+    ///
+    /// Compares the offset and stride of the array when the shader binds a struct ending in
+    /// a runtime-sized array, and the minimum binding size otherwise.
+    pub fn check_layout(
+        &self,
+        shader: &crate::Shader,
+        group: u32,
+        binding: u32,
+    ) -> BufferResult<()> {
+        let (module, var, layouter) = shader
+            .binding_layout(group, binding)?
+            .ok_or(BufferError::BindingNotFound { group, binding })?;
+
+        let mismatch = |what, shader, host| BufferError::LayoutMismatch {
+            label: self.label.clone(),
+            group,
+            binding,
+            what,
+            shader,
+            host,
+        };
+
+        let stride = std::mem::size_of::<T>() as u64;
+        if let naga::TypeInner::Struct { members, .. } = &module.types[var.ty].inner {
+            let array = members
+                .last()
+                .and_then(|member| match module.types[member.ty].inner {
+                    naga::TypeInner::Array {
+                        size: naga::ArraySize::Dynamic,
+                        stride,
+                        ..
+                    } => Some((member.offset as u64, stride as u64)),
+                    _ => None,
+                });
+
+            if let Some((shader_offset, shader_stride)) = array {
+                if shader_offset != self.array_offset {
+                    return Err(mismatch("array offset", shader_offset, self.array_offset));
+                }
+                if shader_stride != stride {
+                    return Err(mismatch("array stride", shader_stride, stride));
+                }
+
+                return Ok(());
+            }
+        }
+
+        let shader_size = layouter[var.ty].size as u64;
+        let host_size = self.array_offset + stride;
+        if shader_size != host_size {
+            return Err(mismatch("size", shader_size, host_size));
+        }
+
+        Ok(())
+    }
+
+    /// Pulls the header from the [`GpuStructBuffer`].
+    pub async fn read_header(&self) -> BufferResult<H> {
+        let header_size = std::mem::size_of::<H>() as u64;
+        let copy_size = wgpu::util::align_to(header_size, wgpu::COPY_BUFFER_ALIGNMENT);
+
+        let download =
+            buffers::download_buffer(&self.fw, &self.buf, 0..copy_size, self.label.as_deref())
+                .await?;
+
+        Ok(bytemuck::pod_read_unaligned(
+            &download[..header_size as usize],
+        ))
+    }
+
+    /// Pulls some elements of the array into `buf`, returning how many elements were read.
+    pub async fn read_elements(&self, buf: &mut [T]) -> BufferResult<u64> {
+        let elements = (buf.len() as u64).min(self.capacity);
+        if elements == 0 {
+            return Ok(0);
+        }
+
+        let bytes = elements * std::mem::size_of::<T>() as u64;
+        let copy_end = wgpu::util::align_to(self.array_offset + bytes, wgpu::COPY_BUFFER_ALIGNMENT);
+
+        let download = buffers::download_buffer(
+            &self.fw,
+            &self.buf,
+            self.array_offset..copy_end,
+            self.label.as_deref(),
+        )
+        .await?;
+
+        buf[..elements as usize].copy_from_slice(bytemuck::cast_slice(&download[..bytes as usize]));
+
+        Ok(elements)
+    }
+
+    /// Pulls all the elements of the array into a [`Vec`].
+    pub async fn read_elements_vec(&self) -> BufferResult<Vec<T>> {
+        let mut buf = vec![T::zeroed(); self.capacity as usize];
+        self.read_elements(&mut buf).await?;
+
+        Ok(buf)
+    }
+
+    /// Blocking version of `GpuStructBuffer::read_header()`.
+    pub fn read_header_blocking(&self) -> BufferResult<H> {
+        self.fw.block_on(self.read_header())
+    }
+
+    /// Blocking version of `GpuStructBuffer::read_elements()`.
+    pub fn read_elements_blocking(&self, buf: &mut [T]) -> BufferResult<u64> {
+        self.fw.block_on(self.read_elements(buf))
+    }
+
+    /// Blocking version of `GpuStructBuffer::read_elements_vec()`.
+    pub fn read_elements_vec_blocking(&self) -> BufferResult<Vec<T>> {
+        self.fw.block_on(self.read_elements_vec())
+    }
+
+    /// Writes the header of this [`GpuStructBuffer`]. The operation is instantly offloaded.
+    pub fn write_header(&self, header: &H) {
+        // Padded with zeros up to `wgpu::COPY_BUFFER_ALIGNMENT`, which never reaches the array.
+        let mut bytes = bytemuck::bytes_of(header).to_vec();
+        bytes.resize(
+            wgpu::util::align_to(bytes.len(), wgpu::COPY_BUFFER_ALIGNMENT as usize),
+            0,
+        );
+
+        self.fw.queue.write_buffer(&self.buf, 0, &bytes);
+    }
+
+    /// Writes `buf` into the array of this [`GpuStructBuffer`], returning how many elements were written.
+    /// The operation is instantly offloaded.
+    ///
+    /// This function will attempt to write the entire contents of `buf` unless its length
+    /// exceeds the array capacity, in which case `GpuStructBuffer::capacity()` elements are written.
+    ///
+    /// Unless they fill the whole array, the written bytes must be a multiple of
+    /// `wgpu::COPY_BUFFER_ALIGNMENT`, as padding them would overwrite the next elements.
+    pub fn write_elements(&self, buf: &[T]) -> BufferResult<u64> {
+        let elements = (buf.len() as u64).min(self.capacity);
+        let bytes: &[u8] = bytemuck::cast_slice(&buf[..elements as usize]);
+
+        let size = bytes.len() as u64;
+        if size.is_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT) {
+            self.fw
+                .queue
+                .write_buffer(&self.buf, self.array_offset, bytes);
+        } else if elements == self.capacity {
+            // The padding falls past the array, in the padding of the allocation.
+            let mut bytes = bytes.to_vec();
+            bytes.resize(
+                wgpu::util::align_to(bytes.len(), wgpu::COPY_BUFFER_ALIGNMENT as usize),
+                0,
+            );
+
+            self.fw
+                .queue
+                .write_buffer(&self.buf, self.array_offset, &bytes);
+        } else {
+            return Err(BufferError::UnalignedWrite {
+                label: self.label.clone(),
+                offset: self.array_offset,
+                size,
+            });
+        }
+
+        Ok(elements)
+    }
+}
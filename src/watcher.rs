@@ -0,0 +1,218 @@
+//! Hot reloading of shaders during development.
+//!
+//! A [`ShaderWatcher`] loads shaders from files and remembers their modification times.
+//! Every call to [`ShaderWatcher::poll`] recompiles the shaders whose files changed since
+//! the last one and swaps the pipelines of the [`Kernel`](crate::Kernel)s created from them,
+//! so an application can pick up shader edits while it runs (e.g. once per frame).
+//!
+//! When a new version fails to load or validate, the error is reported by
+//! [`ShaderWatcher::poll`] and the kernels keep running the last valid version.
+//!
+//! A shader stops being watched once it and all the kernels created from it are dropped.
+//!
+//! ```no_run
+//! # use gpgpu::{watcher::ShaderWatcher, *};
+//! # fn main() -> Result<(), gpgpu::Error> {
+//! let fw = Framework::default();
+//! let watcher = ShaderWatcher::new(&fw);
+//! let shader = watcher.watch_wgsl_file("shader.wgsl")?;
+//!
+//! let buf = GpuBuffer::<u32>::with_capacity(&fw, 64);
+//! let desc = DescriptorSet::default().bind_buffer(&buf, GpuBufferUsage::ReadWrite);
+//! let kernel = Kernel::new(&fw, Program::new(&shader, "main").add_descriptor_set(desc));
+//!
+//! loop {
+//!     for reload in watcher.poll() {
+//!         if let Err(err) = reload.result {
+//!             eprintln!("{}", err);
+//!         }
+//!     }
+//!
+//!     kernel.enqueue(64, 1, 1);
+//! #   break;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError, Weak},
+    time::SystemTime,
+};
+
+use crate::{
+    kernel::{ShaderLink, ShaderResult},
+    preprocessor::WgslPreprocessor,
+    Framework, Shader,
+};
+
+/// Reloads shaders when their files change. See the [module documentation](self).
+pub struct ShaderWatcher {
+    fw: Framework,
+    shaders: Mutex<Vec<WatchedShader>>,
+}
+
+/// Outcome of the reload of a shader, returned by [`ShaderWatcher::poll`].
+#[derive(Debug)]
+pub struct ShaderReload {
+    /// Path of the reloaded shader.
+    pub path: PathBuf,
+    /// Number of kernels now running the new version, or the error that prevented it.
+    pub result: ShaderResult<usize>,
+}
+
+/// Source of a watched shader.
+enum WatchedSource {
+    Wgsl,
+    ProcessedWgsl(WgslPreprocessor),
+}
+
+struct WatchedShader {
+    path: PathBuf,
+    source: WatchedSource,
+    /// Files of the shader, `#include`d ones included, with their last seen modification time.
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+    link: Weak<ShaderLink>,
+}
+
+/// Returns the modification time of `path`, if it can be read.
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+impl WatchedShader {
+    /// Loads the shader, returning it along with the files it was read from.
+    fn load(
+        fw: &Framework,
+        path: &Path,
+        source: &WatchedSource,
+    ) -> ShaderResult<(Shader, Vec<PathBuf>)> {
+        match source {
+            WatchedSource::Wgsl => Ok((Shader::from_wgsl_file(fw, path)?, vec![path.to_owned()])),
+            WatchedSource::ProcessedWgsl(preprocessor) => {
+                let processed = preprocessor.process_file(path)?;
+                let files = processed.files().map(Path::to_owned).collect();
+
+                Ok((Shader::from_processed_wgsl(fw, &processed)?, files))
+            }
+        }
+    }
+
+    fn changed(&self) -> bool {
+        self.files
+            .iter()
+            .any(|(path, time)| modified(path) != *time)
+    }
+
+    /// Reloads the shader, which must still be linked.
+    fn reload(&mut self, fw: &Framework, link: &ShaderLink) -> ShaderResult<usize> {
+        // Remembers the current times first, so a broken version is only retried once edited again.
+        for (path, time) in &mut self.files {
+            *time = modified(path);
+        }
+
+        let (shader, files) = Self::load(fw, &self.path, &self.source)?;
+        if files.iter().ne(self.files.iter().map(|(path, _)| path)) {
+            self.files = files
+                .into_iter()
+                .map(|path| {
+                    let time = modified(&path);
+                    (path, time)
+                })
+                .collect();
+        }
+
+        link.update(fw, &shader)
+    }
+}
+
+impl ShaderWatcher {
+    /// Creates a [`ShaderWatcher`] loading shaders with `fw`.
+    pub fn new(fw: &Framework) -> Self {
+        Self {
+            fw: fw.clone(),
+            shaders: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Loads a `WGSL` shader from `path` and watches it.
+    ///
+    /// [`Kernel`](crate::Kernel)s created from the returned [`Shader`] switch to every
+    /// new valid version of the file found by [`ShaderWatcher::poll`].
+    pub fn watch_wgsl_file(&self, path: impl AsRef<Path>) -> ShaderResult<Shader> {
+        self.watch(path.as_ref(), WatchedSource::Wgsl)
+    }
+
+    /// Loads a `WGSL` shader from `path` processed by `preprocessor` and watches it,
+    /// along with every file it `#include`s.
+    ///
+    /// See [`ShaderWatcher::watch_wgsl_file`].
+    pub fn watch_processed_wgsl_file(
+        &self,
+        preprocessor: WgslPreprocessor,
+        path: impl AsRef<Path>,
+    ) -> ShaderResult<Shader> {
+        self.watch(path.as_ref(), WatchedSource::ProcessedWgsl(preprocessor))
+    }
+
+    fn watch(&self, path: &Path, source: WatchedSource) -> ShaderResult<Shader> {
+        // Read before loading, so edits made while loading are not missed.
+        let time = modified(path);
+        let (mut shader, files) = WatchedShader::load(&self.fw, path, &source)?;
+        let files = files
+            .into_iter()
+            .map(|file| {
+                let time = if file == path { time } else { modified(&file) };
+                (file, time)
+            })
+            .collect();
+
+        let link = ShaderLink::attach(&mut shader);
+        self.shaders().push(WatchedShader {
+            path: path.to_owned(),
+            source,
+            files,
+            link: Arc::downgrade(&link),
+        });
+
+        Ok(shader)
+    }
+
+    fn shaders(&self) -> std::sync::MutexGuard<'_, Vec<WatchedShader>> {
+        // Watched shaders are always left consistent, even by a panicking thread.
+        self.shaders.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Reloads the shaders whose files changed since they were last loaded.
+    ///
+    /// Returns a [`ShaderReload`] for every reloaded shader, empty when nothing changed.
+    /// Shaders dropped along with their kernels are no longer watched.
+    pub fn poll(&self) -> Vec<ShaderReload> {
+        let mut shaders = self.shaders();
+        shaders.retain(|shader| shader.link.strong_count() > 0);
+
+        shaders
+            .iter_mut()
+            .filter(|shader| shader.changed())
+            .filter_map(|shader| Some((shader.link.upgrade()?, shader)))
+            .map(|(link, shader)| {
+                let result = shader.reload(&self.fw, &link);
+                if let Ok(kernels) = &result {
+                    log::info!(
+                        "Reloaded shader {} ({} kernels).",
+                        shader.path.display(),
+                        kernels
+                    );
+                }
+
+                ShaderReload {
+                    path: shader.path.clone(),
+                    result,
+                }
+            })
+            .collect()
+    }
+}
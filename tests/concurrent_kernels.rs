@@ -0,0 +1,86 @@
+use std::{sync::Arc, time::Duration};
+
+use gpgpu::BufOps;
+
+const THREADS: u32 = 8; // Threads enqueuing the same kernel
+const ENQUEUES: u32 = 100; // Enqueues per thread
+const WORKGROUPS: u32 = 16; // Workgroups per enqueue (32 invocations each)
+const LEN: u32 = 1024; // Elements scaled by each thread
+
+/// Creates a [`gpgpu::Framework`] on a software adapter (e.g. llvmpipe, WARP or SwiftShader),
+/// which makes the stress reproducible on any machine.
+///
+/// # Panics
+/// Panics when no software adapter is available, rather than passing without running.
+fn software_framework() -> gpgpu::Framework {
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+        backends: wgpu::Backends::all(),
+        ..Default::default()
+    });
+    let adapter =
+        futures::executor::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            force_fallback_adapter: true,
+            ..Default::default()
+        }))
+        .expect(
+            "concurrent_kernels requires a software adapter (e.g. llvmpipe, WARP or SwiftShader)",
+        );
+
+    futures::executor::block_on(gpgpu::Framework::new(adapter, Duration::from_millis(1)))
+}
+
+#[test]
+fn concurrent_kernels() {
+    let fw = software_framework();
+
+    let shader = gpgpu::Shader::from_wgsl_file(&fw, "tests/shaders/atomic_counter.wgsl").unwrap();
+    let scale_shader = gpgpu::Shader::from_wgsl_file(&fw, "tests/shaders/scale.wgsl").unwrap();
+    let counter = gpgpu::GpuAtomicCounter::new(&fw, 0);
+
+    // A single kernel shared by every thread
+    let desc = gpgpu::DescriptorSet::default().bind_atomic_counter(&counter);
+    let program = gpgpu::Program::new(&shader, "main").add_descriptor_set(desc);
+    let kernel = Arc::new(gpgpu::Kernel::new(&fw, program));
+
+    std::thread::scope(|scope| {
+        for _ in 0..THREADS {
+            let kernel = Arc::clone(&kernel);
+
+            scope.spawn(move || {
+                for _ in 0..ENQUEUES {
+                    kernel.enqueue(WORKGROUPS, 1, 1);
+                }
+            });
+        }
+
+        // Meanwhile, other threads create and run their own kernels on the same framework
+        for id in 0..THREADS {
+            let fw = fw.clone();
+            let (shader, scale_shader) = (&shader, &scale_shader);
+
+            scope.spawn(move || {
+                let data = (0..LEN).map(|x| x * id).collect::<Vec<u32>>();
+                let input = gpgpu::GpuBuffer::from_slice(&fw, &data);
+                let output = gpgpu::GpuBuffer::<u32>::with_capacity(&fw, LEN as u64);
+                let local_counter = gpgpu::GpuAtomicCounter::new(&fw, 0);
+
+                let desc = gpgpu::DescriptorSet::default().bind_atomic_counter(&local_counter);
+                let program = gpgpu::Program::new(shader, "main").add_descriptor_set(desc);
+                gpgpu::Kernel::new(&fw, program).enqueue(WORKGROUPS, 1, 1);
+
+                let desc = gpgpu::DescriptorSet::default()
+                    .bind_buffer(&input, gpgpu::GpuBufferUsage::ReadOnly)
+                    .bind_buffer(&output, gpgpu::GpuBufferUsage::ReadWrite);
+                let program = gpgpu::Program::new(scale_shader, "main").add_descriptor_set(desc);
+                gpgpu::Kernel::new(&fw, program).enqueue(LEN / 32, 1, 1);
+
+                let scaled = data.iter().map(|x| x * 2).collect::<Vec<u32>>();
+                assert_eq!(output.read_vec_blocking().unwrap(), scaled);
+                assert_eq!(local_counter.read_blocking().unwrap(), WORKGROUPS * 32);
+            });
+        }
+    });
+
+    let expected = THREADS * ENQUEUES * WORKGROUPS * 32;
+    assert_eq!(counter.read_blocking().unwrap(), expected);
+}